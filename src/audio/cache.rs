@@ -1,29 +1,151 @@
-use std::{io, path::Path};
+use std::{collections::HashSet, io, path::Path};
 
+use chrono::{DateTime, Utc};
 use songbird::input::{File, Input};
+use tokio::fs;
 
-use super::{AudioSource, AudioSourceError, AudioSourceKind, SCDL_CACHE, YTDL_CACHE};
+use crate::store::{AudioCacheEntry, Store};
+
+use super::{lyrics, provider, AudioSource, AudioSourceError, AudioSourceKind};
 
 pub struct AudioCache;
 
 impl AudioCache {
-    pub fn exists(kind: AudioSourceKind, id: impl AsRef<str>) -> io::Result<bool> {
-        let id = id.as_ref();
+    pub fn path(kind: AudioSourceKind, id: &str) -> String {
+        format!("{}/{}", kind.cache_dir(), id)
+    }
 
-        let p = match kind {
-            AudioSourceKind::YouTube => format!("{YTDL_CACHE}/{}", id),
-            AudioSourceKind::SoundCloud => format!("{SCDL_CACHE}/{}", id),
-        };
+    /// 받고 있는 중인 파일
+    pub fn download_path(kind: AudioSourceKind, id: &str) -> String {
+        format!("{}/{}.download", kind.cache_dir(), id)
+    }
 
-        Path::new(&p).try_exists()
+    pub fn exists(kind: AudioSourceKind, id: impl AsRef<str>) -> io::Result<bool> {
+        Path::new(&Self::path(kind, id.as_ref())).try_exists()
     }
 
     pub async fn get_source(audio_source: &AudioSource) -> Result<Input, AudioSourceError> {
-        let file_path = match audio_source {
-            AudioSource::YouTube(x) => format!("{YTDL_CACHE}/{}", x.id),
-            AudioSource::SoundCloud(x) => format!("{SCDL_CACHE}/{}", x.id),
-        };
+        let file_path = Self::path(audio_source.metadata().kind(), &audio_source.metadata().id);
 
         Ok(Input::from(File::new(file_path)))
     }
+
+    /// 재생한 파일의 크기와 마지막으로 재생한 시간을 기록함
+    pub async fn touch(store: &Store, kind: AudioSourceKind, id: &str) -> crate::Result<()> {
+        let size = fs::metadata(Self::path(kind, id)).await?.len();
+
+        store
+            .audio_cache()
+            .touch(&AudioCacheEntry {
+                kind,
+                id: id.to_string(),
+                size,
+                accessed_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// 캐시 디렉터리의 파일과 기록을 맞춤
+    ///
+    /// 기록되지 않은 파일은 수정된 시간을 마지막으로 재생한 시간으로 기록하고
+    /// 파일이 없는 기록은 지움
+    ///
+    /// 시작할 때만 호출해야 함. 받다가 종료되어 남은 파일도 지움
+    pub async fn sync(store: &Store) -> crate::Result<()> {
+        let mut files = HashSet::new();
+
+        for kind in provider::all().map(|x| x.kind()) {
+            let mut dir = fs::read_dir(kind.cache_dir()).await?;
+
+            while let Some(entry) = dir.next_entry().await? {
+                let metadata = entry.metadata().await?;
+                let id = entry.file_name().to_string_lossy().into_owned();
+
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                if id.ends_with(".download") || id.ends_with(".part") || id.ends_with(".ytdl") {
+                    fs::remove_file(entry.path()).await?;
+                    continue;
+                }
+
+                // 자막은 음악을 지울 때 같이 지움
+                if lyrics::is_lyrics_file(&id) {
+                    continue;
+                }
+
+                store
+                    .audio_cache()
+                    .add_if_absent(&AudioCacheEntry {
+                        kind,
+                        id: id.clone(),
+                        size: metadata.len(),
+                        accessed_at: metadata
+                            .modified()
+                            .map(DateTime::<Utc>::from)
+                            .unwrap_or_else(|_| Utc::now()),
+                    })
+                    .await?;
+
+                files.insert((kind, id));
+            }
+        }
+
+        for entry in store.audio_cache().list().await? {
+            if !files.contains(&(entry.kind, entry.id.clone())) {
+                store.audio_cache().remove(entry.kind, &entry.id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 캐시 용량이 max_bytes를 넘으면 가장 오래 재생하지 않은 파일부터 지움
+    ///
+    /// 재생 중인 파일은 지우지 않음
+    pub async fn evict(
+        store: &Store,
+        max_bytes: u64,
+        playing: &HashSet<(AudioSourceKind, String)>,
+    ) -> crate::Result<()> {
+        let mut total_size = store.audio_cache().total_size().await?;
+
+        if total_size <= max_bytes {
+            return Ok(());
+        }
+
+        for entry in store.audio_cache().list().await? {
+            if total_size <= max_bytes {
+                break;
+            }
+
+            if playing.contains(&(entry.kind, entry.id.clone())) {
+                continue;
+            }
+
+            match fs::remove_file(Self::path(entry.kind, &entry.id)).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            lyrics::remove(entry.kind, &entry.id).await;
+
+            store.audio_cache().remove(entry.kind, &entry.id).await?;
+
+            total_size = total_size.saturating_sub(entry.size);
+
+            log::info!(
+                "evicted {}/{} ({} bytes)",
+                entry.kind.as_str(),
+                entry.id,
+                entry.size
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,179 @@
+//! ffmpeg로 캐시된 파일에 효과를 적용해서 재생함
+//!
+//! ffmpeg가 내보내는 PCM은 songbird로 재생 위치를 옮길 수 없기 때문에
+//! 재생 위치를 옮기거나 필터를 바꾸면 그 위치부터 ffmpeg를 다시 실행함
+
+use std::{
+    process::{Command, Stdio},
+    str::FromStr,
+    time::Duration,
+};
+
+use songbird::input::{
+    core::io::{MediaSource, ReadOnlySource},
+    AudioStream, AudioStreamError, ChildContainer, Compose, Input, RawAdapter,
+};
+
+pub const FFMPEG: &str = "ffmpeg";
+
+const SAMPLE_RATE: u32 = 48000;
+const CHANNELS: u32 = 2;
+
+const BASS_BOOST: &str = "bass=g=8:f=110:w=0.6";
+const NIGHTCORE: &str = "aresample=48000,asetrate=60000,aresample=48000";
+const SLOWED: &str = "aresample=48000,asetrate=40800,aresample=48000";
+const REVERB: &str = "aecho=0.8:0.7:40|60|90:0.35|0.25|0.2";
+const EIGHT_D: &str = "apulsator=hz=0.125";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    #[default]
+    Off,
+    BassBoost,
+    Nightcore,
+    Slowed,
+    Reverb,
+    SlowedReverb,
+    EightD,
+}
+
+impl Filter {
+    pub const ALL: [Filter; 7] = [
+        Filter::Off,
+        Filter::BassBoost,
+        Filter::Nightcore,
+        Filter::Slowed,
+        Filter::Reverb,
+        Filter::SlowedReverb,
+        Filter::EightD,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        use Filter::*;
+
+        match self {
+            Off => "off",
+            BassBoost => "bassboost",
+            Nightcore => "nightcore",
+            Slowed => "slowed",
+            Reverb => "reverb",
+            SlowedReverb => "slowed_reverb",
+            EightD => "8d",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        use Filter::*;
+
+        match self {
+            Off => "끄기",
+            BassBoost => "베이스 부스트",
+            Nightcore => "나이트코어",
+            Slowed => "슬로우",
+            Reverb => "리버브",
+            SlowedReverb => "슬로우 + 리버브",
+            EightD => "8D",
+        }
+    }
+
+    /// 원래 음악보다 빠르게 재생하는 배율
+    ///
+    /// 재생 위치를 원래 음악의 위치로 바꿀 때 사용함
+    pub fn speed(&self) -> f64 {
+        match self {
+            Filter::Nightcore => 1.25,
+            Filter::Slowed | Filter::SlowedReverb => 0.85,
+            _ => 1.0,
+        }
+    }
+
+    fn af(&self) -> String {
+        use Filter::*;
+
+        match self {
+            Off => "anull".to_string(),
+            BassBoost => BASS_BOOST.to_string(),
+            Nightcore => NIGHTCORE.to_string(),
+            Slowed => SLOWED.to_string(),
+            Reverb => REVERB.to_string(),
+            SlowedReverb => format!("{SLOWED},{REVERB}"),
+            EightD => EIGHT_D.to_string(),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::ALL.into_iter().find(|x| x.as_str() == s).ok_or(())
+    }
+}
+
+/// 다 받은 파일을 start부터 필터를 적용해서 재생함
+pub fn get_source(path: impl Into<String>, filter: Filter, start: Duration) -> Input {
+    Input::Lazy(Box::new(FilteredFile {
+        path: path.into(),
+        filter,
+        start: Some(start),
+    }))
+}
+
+struct FilteredFile {
+    path: String,
+    filter: Filter,
+    /// 반복 재생하면 처음부터 다시 만들기 때문에 처음 만들 때만 사용함
+    start: Option<Duration>,
+}
+
+#[async_trait::async_trait]
+impl Compose for FilteredFile {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let start = self.start.take().unwrap_or_default();
+
+        let child = Command::new(FFMPEG)
+            .args(["-nostdin", "-loglevel", "error"])
+            .args(["-ss", &format!("{:.3}", start.as_secs_f64())])
+            .args(["-i", &self.path])
+            .args(["-af", &self.filter.af()])
+            .args(["-f", "f32le"])
+            .args(["-ar", &SAMPLE_RATE.to_string()])
+            .args(["-ac", &CHANNELS.to_string()])
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| AudioStreamError::Fail(Box::new(err)))?;
+
+        let source = RawAdapter::new(
+            ReadOnlySource::new(ChildContainer::from(child)),
+            SAMPLE_RATE,
+            CHANNELS,
+        );
+
+        Ok(AudioStream {
+            input: Box::new(source),
+            hint: None,
+        })
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    fn should_create_async(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn test_filter_from_str() {
+    for filter in Filter::ALL {
+        assert_eq!(filter.as_str().parse(), Ok(filter));
+    }
+
+    assert_eq!("loud".parse::<Filter>(), Err(()));
+}
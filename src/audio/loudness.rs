@@ -0,0 +1,291 @@
+//! 캐시된 음악의 음량을 EBU R128 (ITU-R BS.1770) 방식으로 측정함
+//!
+//! 측정한 음량이 목표 음량보다 크면 기본 소리 크기를 줄이고 작으면 키움
+
+use std::{f64::consts::PI, fs::File};
+
+use songbird::input::codecs::{get_codec_registry, get_probe};
+use symphonia::core::{
+    audio::SampleBuffer, codecs::DecoderOptions, errors::Error as SymphoniaError,
+    formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+};
+
+use super::AudioSourceError;
+
+/// 기본 소리 크기에서 키우거나 줄일 수 있는 최대 크기 (dB)
+const MAX_GAIN_DB: f64 = 12.0;
+
+/// 이보다 작은 블록은 무음으로 보고 측정하지 않음 (LUFS)
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// 전체 음량보다 이만큼 작은 블록은 측정하지 않음 (LU)
+const RELATIVE_GATE: f64 = -10.0;
+
+/// 재생할 소리 크기
+#[derive(Debug, Clone, Copy)]
+pub enum Volume {
+    /// 입력했거나 음악마다 기록된 소리 크기
+    Fixed(f32),
+    /// 측정한 음량으로 정했거나 측정하기 전이라 기본 소리 크기를 사용함. 음악마다 기록하지 않음
+    Auto(f32),
+}
+
+impl Volume {
+    pub fn get(&self) -> f32 {
+        match self {
+            Self::Fixed(x) | Self::Auto(x) => *x,
+        }
+    }
+
+    /// 음악마다 기록할 소리 크기
+    pub fn fixed(&self) -> Option<f32> {
+        match self {
+            Self::Fixed(x) => Some(*x),
+            Self::Auto(_) => None,
+        }
+    }
+}
+
+/// 목표 음량(LUFS)의 음악을 기본 소리 크기(default_volume)로 재생하도록 소리 크기를 정함
+///
+/// 소리 크기는 1.0(100%)을 넘지 않음
+pub fn normalize(integrated: f64, target: f64, default_volume: f32) -> f32 {
+    let gain_db = (target - integrated).clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+
+    (default_volume * 10f64.powf(gain_db / 20.0) as f32).min(1.0)
+}
+
+/// 파일을 모두 디코딩해서 음량(LUFS)을 측정함
+///
+/// 무음이면 None
+pub fn measure(path: &str) -> Result<Option<f64>, AudioSourceError> {
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+
+    let mut probed = get_probe().format(
+        &Hint::new(),
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    let track = probed
+        .format
+        .default_track()
+        .ok_or(SymphoniaError::Unsupported("no track"))?;
+    let track_id = track.id;
+
+    let mut decoder = get_codec_registry().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut meter = None;
+    let mut buf = None;
+
+    loop {
+        let packet = match probed.format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(err))
+                if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(err) => return Err(err.into()),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // 깨진 패킷은 건너뜀
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        let spec = *decoded.spec();
+
+        let meter = meter.get_or_insert_with(|| Meter::new(spec.rate, spec.channels.count()));
+        let buf =
+            buf.get_or_insert_with(|| SampleBuffer::<f32>::new(decoded.capacity() as u64, spec));
+
+        if buf.capacity() < decoded.frames() * spec.channels.count() {
+            *buf = SampleBuffer::new(decoded.capacity() as u64, spec);
+        }
+
+        buf.copy_interleaved_ref(decoded);
+        meter.push(buf.samples());
+    }
+
+    Ok(meter.and_then(|x| x.integrated()))
+}
+
+/// 2차 IIR 필터
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+
+        y
+    }
+}
+
+/// 샘플링 주파수에 맞춘 K-weighting 필터 (고역 강조, 저역 차단)
+fn k_weighting(rate: u32) -> [Biquad; 2] {
+    let rate = rate as f64;
+
+    let f0 = 1681.974450955533;
+    let g = 3.999843853973347;
+    let q = 0.7071752369554196;
+
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+/// 100ms마다 K-weighting한 샘플의 제곱합을 모아 두고
+/// 75%씩 겹치는 400ms 블록으로 음량을 계산함
+struct Meter {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    /// 100ms의 프레임 수
+    step: usize,
+    steps: Vec<f64>,
+    energy: f64,
+    frames: usize,
+}
+
+impl Meter {
+    fn new(rate: u32, channels: usize) -> Self {
+        Self {
+            channels,
+            filters: vec![k_weighting(rate); channels],
+            step: (rate as usize / 10).max(1),
+            steps: Vec::new(),
+            energy: 0.0,
+            frames: 0,
+        }
+    }
+
+    /// 채널이 번갈아 있는 샘플
+    fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (x, [shelf, high_pass]) in frame.iter().zip(&mut self.filters) {
+                let y = high_pass.process(shelf.process(*x as f64));
+
+                self.energy += y * y;
+            }
+
+            self.frames += 1;
+
+            if self.frames == self.step {
+                self.steps.push(self.energy);
+                self.energy = 0.0;
+                self.frames = 0;
+            }
+        }
+    }
+
+    fn integrated(&self) -> Option<f64> {
+        let blocks = self
+            .steps
+            .windows(4)
+            .map(|x| x.iter().sum::<f64>() / (4 * self.step) as f64)
+            .filter(|x| loudness(*x) > ABSOLUTE_GATE)
+            .collect::<Vec<_>>();
+
+        if blocks.is_empty() {
+            return None;
+        }
+
+        let relative_gate = loudness(mean(&blocks)) + RELATIVE_GATE;
+
+        let blocks = blocks
+            .into_iter()
+            .filter(|x| loudness(*x) > relative_gate)
+            .collect::<Vec<_>>();
+
+        Some(loudness(mean(&blocks)))
+    }
+}
+
+fn loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+#[test]
+fn test_integrated_loudness() {
+    // EBU Tech 3341: -23 dBFS, 1 kHz 스테레오 사인파는 -23 LUFS
+    let rate = 48000;
+    let amplitude = 10f64.powf(-23.0 / 20.0);
+
+    let samples = (0..rate * 5)
+        .flat_map(|i| {
+            let x = (amplitude * (2.0 * PI * 1000.0 * i as f64 / rate as f64).sin()) as f32;
+            [x, x]
+        })
+        .collect::<Vec<_>>();
+
+    let mut meter = Meter::new(rate, 2);
+    meter.push(&samples);
+
+    let integrated = meter.integrated().unwrap();
+
+    assert!((integrated + 23.0).abs() < 0.1, "{integrated}");
+
+    let mut meter = Meter::new(rate, 2);
+    meter.push(&vec![0.0; rate as usize * 2 * 5]);
+
+    assert_eq!(meter.integrated(), None);
+}
+
+#[test]
+fn test_normalize() {
+    let default_volume = 0.05;
+
+    assert_eq!(normalize(-14.0, -14.0, default_volume), default_volume);
+    assert!(normalize(-8.0, -14.0, default_volume) < default_volume);
+    assert!(normalize(-20.0, -14.0, default_volume) > default_volume);
+    assert_eq!(
+        normalize(-60.0, -14.0, default_volume),
+        normalize(-26.0, -14.0, default_volume)
+    );
+    assert_eq!(normalize(-60.0, -14.0, 0.5), 1.0);
+}
@@ -0,0 +1,347 @@
+//! yt-dlp로 받은 자막을 가사로 사용함
+//!
+//! 음악을 받을 때 캐시 디렉터리에 `{id}.lyrics.{lang}.{ext}`로 같이 받아 둠
+
+use std::{process::Stdio, time::Duration};
+
+use tokio::{fs, process::Command};
+
+use super::{AudioSourceKind, YTDL};
+
+/// 먼저 찾는 언어. 없으면 자동 생성된 원래 언어의 자막을 사용함
+const LANGS: [&str; 3] = ["ko", "en", "ja"];
+
+const SUB_LANGS: &str = "ko,en,ja,.*-orig";
+const SUB_FORMAT: &str = "vtt/srv3/srv2/srv1/best";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub start: Duration,
+    pub text: String,
+}
+
+fn prefix(id: &str) -> String {
+    format!("{id}.lyrics.")
+}
+
+/// 자막 파일이면 true. 캐시 디렉터리의 음악 파일과 구분할 때 사용함
+pub fn is_lyrics_file(file_name: &str) -> bool {
+    file_name.contains(".lyrics.")
+}
+
+/// 음악을 받는 동안 자막을 같이 받음
+///
+/// 자막이 없거나 받지 못해도 재생에는 영향이 없음
+pub async fn download(kind: AudioSourceKind, id: String, url: String) {
+    let output = format!("{}/{}%(ext)s", kind.cache_dir(), prefix(&id));
+
+    let r = Command::new(YTDL)
+        .args(["--skip-download", "--write-subs", "--write-auto-subs"])
+        .args(["--sub-langs", SUB_LANGS])
+        .args(["--sub-format", SUB_FORMAT])
+        .args(["--output", &output])
+        .args(["--no-progress", "--quiet", "--no-playlist"])
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await;
+
+    match r {
+        Ok(status) if status.success() => {}
+        Ok(status) => log::warn!("failed to download lyrics {}/{id}: {status}", kind.as_str()),
+        Err(err) => log::warn!("failed to download lyrics {}/{id}: {err}", kind.as_str()),
+    }
+}
+
+/// 받아 둔 자막 중에서 먼저 찾는 언어의 자막을 읽음
+pub async fn load(kind: AudioSourceKind, id: &str) -> Option<Vec<Line>> {
+    let files = list(kind, id).await;
+
+    let (file_name, _) = files.into_iter().min_by_key(|(file_name, lang)| {
+        let priority = LANGS
+            .iter()
+            .position(|x| lang == x)
+            .or_else(|| LANGS.iter().position(|x| lang.starts_with(x)))
+            .unwrap_or(LANGS.len());
+
+        (priority, file_name.clone())
+    })?;
+
+    let buf = fs::read_to_string(format!("{}/{file_name}", kind.cache_dir()))
+        .await
+        .ok()?;
+
+    let lines = if file_name.ends_with(".vtt") {
+        parse_vtt(&buf)
+    } else {
+        parse_srv(&buf)
+    };
+
+    Some(lines).filter(|x| !x.is_empty())
+}
+
+/// 음악을 캐시에서 지울 때 같이 지움
+pub async fn remove(kind: AudioSourceKind, id: &str) {
+    for (file_name, _) in list(kind, id).await {
+        fs::remove_file(format!("{}/{file_name}", kind.cache_dir()))
+            .await
+            .ok();
+    }
+}
+
+/// (파일 이름, 언어)
+async fn list(kind: AudioSourceKind, id: &str) -> Vec<(String, String)> {
+    let prefix = prefix(id);
+    let mut r = Vec::new();
+
+    let Ok(mut dir) = fs::read_dir(kind.cache_dir()).await else {
+        return r;
+    };
+
+    while let Ok(Some(entry)) = dir.next_entry().await {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+
+        let lang = file_name
+            .strip_prefix(&prefix)
+            .and_then(|x| x.rsplit_once('.'))
+            .map(|(lang, _ext)| lang.to_string());
+
+        if let Some(lang) = lang {
+            r.push((file_name, lang));
+        }
+    }
+
+    r
+}
+
+/// 재생 시간에 불러야 하는 줄의 순서
+pub fn current(lines: &[Line], position: Duration) -> Option<usize> {
+    lines
+        .partition_point(|x| x.start <= position)
+        .checked_sub(1)
+}
+
+/// WebVTT
+///
+/// 자동 생성된 자막은 앞의 줄을 다음 자막에서 반복하기 때문에 바로 앞과 같은 줄은 건너뜀
+pub fn parse_vtt(buf: &str) -> Vec<Line> {
+    let mut lines = Vec::<Line>::new();
+    let mut start = None;
+
+    for x in buf.lines() {
+        // 자동 생성된 자막에는 공백만 있는 줄이 있어서 빈 줄만 자막의 끝으로 봄
+        if x.is_empty() {
+            start = None;
+            continue;
+        }
+
+        let x = x.trim();
+
+        if let Some((from, _)) = x.split_once("-->") {
+            start = parse_timestamp(from.trim());
+            continue;
+        }
+
+        let Some(start) = start else {
+            continue;
+        };
+
+        let text = decode_entities(&strip_tags(x));
+        let text = text.trim();
+
+        if text.is_empty() || lines.last().is_some_and(|x| x.text == text) {
+            continue;
+        }
+
+        lines.push(Line {
+            start,
+            text: text.to_string(),
+        });
+    }
+
+    lines.sort_by_key(|x| x.start);
+    lines
+}
+
+/// 유튜브의 srv1 (`<text start="1.5" dur="2">`), srv2, srv3 (`<p t="1500" d="2000">`)
+pub fn parse_srv(buf: &str) -> Vec<Line> {
+    let mut lines = Vec::<Line>::new();
+    let mut rest = buf;
+
+    while let Some(i) = rest.find('<') {
+        rest = &rest[i + 1..];
+
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || c == '>')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+
+        if name != "text" && name != "p" {
+            continue;
+        }
+
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let attrs = &rest[name_len..tag_end];
+
+        let close = format!("</{name}>");
+        let Some(content_end) = rest[tag_end..].find(&close) else {
+            break;
+        };
+        let content = &rest[tag_end + 1..tag_end + content_end];
+
+        rest = &rest[tag_end + content_end + close.len()..];
+
+        let start = match name {
+            "text" => attr(attrs, "start")
+                .and_then(|x| x.parse::<f64>().ok())
+                .or_else(|| {
+                    attr(attrs, "t")
+                        .and_then(|x| x.parse::<f64>().ok())
+                        .map(|x| x / 1000.0)
+                }),
+            _ => attr(attrs, "t")
+                .and_then(|x| x.parse::<f64>().ok())
+                .map(|x| x / 1000.0),
+        };
+
+        let Some(start) = start.filter(|x| x.is_finite() && *x >= 0.0) else {
+            continue;
+        };
+
+        // srv1은 두 번 이스케이프되어 있음
+        let text = decode_entities(&decode_entities(&strip_tags(content)));
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if text.is_empty() || lines.last().is_some_and(|x| x.text == text) {
+            continue;
+        }
+
+        lines.push(Line {
+            start: Duration::from_secs_f64(start),
+            text,
+        });
+    }
+
+    lines.sort_by_key(|x| x.start);
+    lines
+}
+
+fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{name}=\"");
+
+    let mut rest = attrs;
+
+    loop {
+        let i = rest.find(&pattern)?;
+
+        // `t`가 `dt`에서 찾아지지 않도록 앞이 공백인지 확인함
+        if i == 0 || rest[..i].ends_with(char::is_whitespace) {
+            let value = &rest[i + pattern.len()..];
+            return value.find('"').map(|end| &value[..end]);
+        }
+
+        rest = &rest[i + pattern.len()..];
+    }
+}
+
+/// 00:01:02.345, 01:02.345
+fn parse_timestamp(x: &str) -> Option<Duration> {
+    let (hms, millis) = x.split_once('.')?;
+
+    let secs = hms
+        .split(':')
+        .try_fold(0u64, |acc, x| Some(acc * 60 + x.parse::<u64>().ok()?))?;
+
+    Some(Duration::from_secs(secs) + Duration::from_millis(millis.parse().ok()?))
+}
+
+fn strip_tags(x: &str) -> String {
+    let mut r = String::with_capacity(x.len());
+    let mut in_tag = false;
+
+    for c in x.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => r.push(c),
+            _ => {}
+        }
+    }
+
+    r
+}
+
+fn decode_entities(x: &str) -> String {
+    x.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn test_parse_vtt() {
+    let buf = "WEBVTT\nKind: captions\nLanguage: en\n\n\
+        00:00:01.000 --> 00:00:03.000 align:start position:0%\n \n\
+        hello<00:00:01.500><c> world</c>\n\n\
+        00:00:03.000 --> 00:00:03.010 align:start position:0%\n\
+        hello world\n \n\n\
+        00:00:03.010 --> 00:00:05.000 align:start position:0%\n\
+        hello world\n\
+        rock &amp; roll\n";
+
+    assert_eq!(
+        parse_vtt(buf),
+        vec![
+            Line {
+                start: Duration::from_secs(1),
+                text: "hello world".to_string()
+            },
+            Line {
+                start: Duration::from_millis(3010),
+                text: "rock & roll".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_srv() {
+    let srv1 = r#"<?xml version="1.0" encoding="utf-8" ?><transcript><text start="0.5" dur="2">first</text><text start="2.5" dur="1.5">it&amp;#39;s</text></transcript>"#;
+
+    assert_eq!(
+        parse_srv(srv1)
+            .into_iter()
+            .map(|x| (x.start, x.text))
+            .collect::<Vec<_>>(),
+        vec![
+            (Duration::from_millis(500), "first".to_string()),
+            (Duration::from_millis(2500), "it's".to_string()),
+        ]
+    );
+
+    let srv3 = r#"<timedtext format="3"><body><p t="1000" d="2000" wp="1"><s>first</s><s t="500"> line</s></p><p t="3000" d="10"></p><p t="4000" d="1000">second</p></body></timedtext>"#;
+
+    assert_eq!(
+        parse_srv(srv3)
+            .into_iter()
+            .map(|x| (x.start, x.text))
+            .collect::<Vec<_>>(),
+        vec![
+            (Duration::from_secs(1), "first line".to_string()),
+            (Duration::from_secs(4), "second".to_string()),
+        ]
+    );
+
+    let lines = parse_srv(r#"<p t="1000">a</p><p t="2000">b</p>"#);
+
+    assert_eq!(current(&lines, Duration::ZERO), None);
+    assert_eq!(current(&lines, Duration::from_millis(1500)), Some(0));
+    assert_eq!(current(&lines, Duration::from_secs(5)), Some(1));
+}
@@ -3,11 +3,30 @@ use std::{collections::HashMap, time::Duration};
 use crate::{audio::ytdl::Thumbnail, util::time::parse_iso8601_duration};
 
 use super::{
+    provider::{Provider, SoundCloud, YouTube},
     scdl,
-    ytdl::{SearchItem, VideoItem},
+    ytdl::{PlaylistItemsItem, SearchItem, VideoItem},
     AudioSourceKind,
 };
 
+/// 유튜브 재생목록, 사운드클라우드 세트
+#[derive(Debug, Clone)]
+pub struct AudioPlaylist {
+    pub title: String,
+    pub url: String,
+    pub items: Vec<AudioMetadata>,
+}
+
+impl From<scdl::Playlist> for AudioPlaylist {
+    fn from(x: scdl::Playlist) -> Self {
+        Self {
+            title: x.title,
+            url: x.permalink_url,
+            items: x.tracks.into_iter().map(AudioMetadata::from).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AudioMetadata {
     pub id: String,
@@ -36,7 +55,7 @@ impl From<scdl::Track> for AudioMetadata {
             uploaded_by: x.user.username,
 
             duration: Some(Duration::from_millis(x.duration)),
-            _kind: AudioSourceKind::SoundCloud,
+            _kind: SoundCloud.kind(),
         }
     }
 }
@@ -63,7 +82,34 @@ impl TryFrom<VideoItem> for AudioMetadata {
                     .content_details?
                     .duration
                     .and_then(|d| parse_iso8601_duration(&d)),
-                _kind: AudioSourceKind::YouTube,
+                _kind: YouTube.kind(),
+            })
+        }
+
+        temp(item).ok_or(())
+    }
+}
+
+impl TryFrom<PlaylistItemsItem> for AudioMetadata {
+    type Error = ();
+
+    fn try_from(item: PlaylistItemsItem) -> Result<Self, Self::Error> {
+        fn temp(item: PlaylistItemsItem) -> Option<AudioMetadata> {
+            let snippet = item.snippet?;
+            let video_id = snippet.resource_id?.video_id?;
+
+            let url = format!("https://www.youtube.com/watch?v={}", video_id);
+            let thumbnail = find_highest_thumbnail(snippet.thumbnails);
+
+            Some(AudioMetadata {
+                id: video_id,
+                title: snippet.title?,
+                url,
+                thumbnail_url: thumbnail.and_then(|t| t.url),
+                uploaded_by: snippet.video_owner_channel_title?,
+
+                duration: None,
+                _kind: YouTube.kind(),
             })
         }
 
@@ -90,7 +136,7 @@ impl TryFrom<SearchItem> for AudioMetadata {
                 uploaded_by: snippet.channel_title?,
 
                 duration: None,
-                _kind: AudioSourceKind::YouTube,
+                _kind: YouTube.kind(),
             })
         }
 
@@ -1,22 +1,32 @@
 pub mod cache;
+pub mod filter;
+pub mod loudness;
+pub mod lyrics;
 pub mod metadata;
+pub mod progressive;
+pub mod provider;
+pub mod proxy;
 pub mod scdl;
 pub mod ytdl;
+pub mod ytdlp;
 
-pub use metadata::AudioMetadata;
+pub use metadata::{AudioMetadata, AudioPlaylist};
 
-use std::io;
+use std::{io, time::Duration};
 
 use songbird::input::{self, Input};
-use youtube_dl::YoutubeDl;
 
-use crate::store::{History, HistoryKind};
+use crate::store::History;
 
-use self::cache::AudioCache;
+use self::{
+    cache::AudioCache,
+    filter::Filter,
+    provider::{Credentials, Provider},
+};
 
 pub const YTDL: &str = "./yt-dlp";
-pub const YTDL_CACHE: &str = "./cache/youtube";
-pub const SCDL_CACHE: &str = "./cache/soundcloud";
+/// 음악을 가져온 곳마다 하위 디렉터리에 캐시함
+pub const CACHE_DIR: &str = "./cache";
 
 #[derive(Debug, thiserror::Error)]
 pub enum AudioSourceError {
@@ -35,114 +45,135 @@ pub enum AudioSourceError {
     #[error("soundcloud_api: {0}")]
     SoundCloudApiError(#[from] scdl::Error),
 
+    #[error("download: {0}")]
+    DownloadError(String),
+
     #[error("must be video url")]
     MustSingleVideo,
-}
 
-pub enum AudioSource {
-    YouTube(AudioMetadata),
-    SoundCloud(AudioMetadata),
-}
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+
+    #[error("playlist is not supported")]
+    PlaylistNotSupported,
+
+    #[error("symphonia: {0}")]
+    SymphoniaError(#[from] symphonia::core::errors::Error),
 
-#[derive(Debug, Clone, Copy)]
-pub enum AudioSourceKind {
-    YouTube,
-    SoundCloud,
+    #[error("file is too large: max {0} bytes")]
+    FileTooLarge(usize),
+
+    #[error("no metadata")]
+    NoMetadata,
 }
 
-pub fn starts_with_invalid_char(x: &str) -> bool {
-    x.starts_with(['-'])
+impl AudioSourceError {
+    /// 음악의 정보를 가져오지 못했음. 기록된 정보로 대신 재생할 수 있음
+    pub fn is_api_error(&self) -> bool {
+        matches!(
+            self,
+            Self::YouTubeApiError(_) | Self::SoundCloudApiError(_) | Self::NoMetadata
+        )
+    }
 }
 
-impl AudioSource {
-    pub async fn from_youtube(api_key: &str, id: &str) -> Result<Self, AudioSourceError> {
-        if !AudioCache::exists(AudioSourceKind::YouTube, id)? {
-            let starts_with_invalid_char = starts_with_invalid_char(id);
-
-            YoutubeDl::new(if starts_with_invalid_char {
-                // format!("ytsearch:{id}")
-                format!("https://youtu.be/{id}")
-            } else {
-                id.to_string()
-            })
-            .youtube_dl_path(YTDL)
-            .format("webm[abr>0]/bestaudio/best")
-            .output_template("%(id)s")
-            .download_to_async(YTDL_CACHE)
-            .await?;
+/// 재생할 수 있도록 받기 시작한 음악
+pub struct AudioSource(AudioMetadata);
+
+/// 음악을 가져온 곳의 id. `provider::Provider::id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AudioSourceKind(&'static str);
+
+impl From<String> for AudioSourceKind {
+    fn from(x: String) -> Self {
+        match provider::get(&x) {
+            Some(provider) => provider.kind(),
+            None => unreachable!("unknown provider: {x}"),
         }
+    }
+}
 
-        let x = ytdl::get(api_key, id).await?;
+impl AudioSourceKind {
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn provider(&self) -> &'static dyn Provider {
+        provider::get(self.0).unwrap()
+    }
 
-        Ok(Self::YouTube(x))
+    pub fn cache_dir(&self) -> String {
+        format!("{CACHE_DIR}/{}", self.0)
     }
+}
+
+impl From<AudioMetadata> for AudioSource {
+    fn from(metadata: AudioMetadata) -> Self {
+        Self(metadata)
+    }
+}
 
-    pub async fn from_soundcloud(
-        client_id: &str,
-        track_url: &str,
+impl AudioSource {
+    /// 받기 시작한 다음에 음악의 정보를 가져옴
+    pub async fn new(
+        credentials: &Credentials,
+        kind: AudioSourceKind,
+        id: &str,
+        url: &str,
     ) -> Result<Self, AudioSourceError> {
-        let track = scdl::get_track(client_id, track_url).await?;
-        let track_id = track.id.to_string();
-
-        if !AudioCache::exists(AudioSourceKind::SoundCloud, &track_id)? {
-            YoutubeDl::new(track_url)
-                .to_owned()
-                .youtube_dl_path(YTDL)
-                .format("webm[abr>0]/bestaudio/best")
-                .output_template("%(id)s")
-                .download_to_async(SCDL_CACHE)
-                .await?;
-        }
+        let provider = kind.provider();
+
+        provider.download(id, url).await?;
 
-        Ok(Self::SoundCloud(track.into()))
+        let metadata = provider.get_metadata(credentials, id, url).await?;
+
+        Ok(Self(metadata))
     }
 
-    pub fn from_history(
-        History {
-            title,
-            channel,
-            kind,
-            uid,
-            ..
-        }: History,
-    ) -> Self {
-        let (kind, url) = match kind {
-            HistoryKind::YouTube => (
-                AudioSourceKind::YouTube,
-                format!("https://youtu.be/{}", uid),
-            ),
-            HistoryKind::SoundCloud => (
-                AudioSourceKind::SoundCloud,
-                format!("https://api-v2.soundcloud.com/tracks/{}", uid),
-            ),
-        };
-        let metadata = AudioMetadata {
-            id: uid,
-            title,
-            url,
-            thumbnail_url: None,
-            uploaded_by: channel,
-            duration: None,
-            _kind: kind,
-        };
-
-        match kind {
-            AudioSourceKind::YouTube => AudioSource::YouTube(metadata),
-            AudioSourceKind::SoundCloud => AudioSource::SoundCloud(metadata),
-        }
+    pub fn from_history(history: History) -> Self {
+        Self(history.kind.provider().metadata_from_history(history))
     }
 
     pub fn metadata(&self) -> &AudioMetadata {
-        match self {
-            Self::YouTube(x) => x,
-            Self::SoundCloud(x) => x,
+        &self.0
+    }
+
+    /// 필터를 적용하려면 다 받을 때까지 기다림
+    ///
+    /// 필터를 끈 경우에는 start부터 재생하지 않으니 재생한 다음에 이동해야 함
+    pub async fn get_filtered_source(
+        &self,
+        filter: Filter,
+        start: Duration,
+    ) -> Result<Input, AudioSourceError> {
+        if filter == Filter::Off {
+            return self.get_source().await;
+        }
+
+        let metadata = self.metadata();
+
+        progressive::wait(metadata.kind(), &metadata.id).await;
+
+        if AudioCache::exists(metadata.kind(), &metadata.id)? {
+            Ok(filter::get_source(
+                AudioCache::path(metadata.kind(), &metadata.id),
+                filter,
+                start,
+            ))
+        } else {
+            Err(AudioSourceError::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                "캐시된 영상을 찾을 수 없습니다",
+            )))
         }
     }
 
     pub async fn get_source(&self) -> Result<Input, AudioSourceError> {
         let metadata = self.metadata();
 
-        if AudioCache::exists(metadata.kind(), &metadata.id)? {
+        if let Some(input) = progressive::get_source(metadata.kind(), &metadata.id) {
+            Ok(input)
+        } else if AudioCache::exists(metadata.kind(), &metadata.id)? {
             Ok(AudioCache::get_source(self).await?)
         } else {
             Err(AudioSourceError::IoError(io::Error::new(
@@ -0,0 +1,312 @@
+//! 받고 있는 중인 파일을 받은 만큼 먼저 재생함
+//!
+//! yt-dlp는 `{id}.download`에 받고, 다 받으면 `{id}`로 이름을 바꿈.
+//! 실패하면 `{id}.download`를 지우기 때문에 `AudioCache::exists`가 잘린 파일을 찾는 일은 없음
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    pin::Pin,
+    process::Stdio,
+    sync::{LazyLock, Mutex},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use songbird::input::{
+    core::io::MediaSource, AsyncAdapterStream, AsyncMediaSource, AudioStream, AudioStreamError,
+    Compose, Input,
+};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncRead, AsyncReadExt, AsyncSeek, ReadBuf},
+    process::Command,
+    sync::watch,
+    time::{sleep, Sleep},
+};
+
+use super::{cache::AudioCache, lyrics, AudioSourceError, AudioSourceKind, YTDL};
+
+const FORMAT: &str = "webm[abr>0]/bestaudio/best";
+
+/// 받은 만큼 다 읽었을 때 다시 읽기까지 기다리는 시간
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DownloadState {
+    /// 아직 받은 내용이 없음
+    Pending,
+    Downloading,
+    Done,
+    Failed(String),
+}
+
+type Downloads = HashMap<(AudioSourceKind, String), watch::Receiver<DownloadState>>;
+
+/// 같은 음악을 여러 서버에서 동시에 재생해도 한 번만 받음
+static DOWNLOADS: LazyLock<Mutex<Downloads>> = LazyLock::new(Default::default);
+
+fn get(kind: AudioSourceKind, id: &str) -> Option<watch::Receiver<DownloadState>> {
+    DOWNLOADS
+        .lock()
+        .unwrap()
+        .get(&(kind, id.to_string()))
+        .cloned()
+}
+
+/// 캐시되지 않은 음악을 받기 시작하고 받은 내용이 생길 때까지 기다림
+pub async fn download(
+    kind: AudioSourceKind,
+    id: &str,
+    url: impl Into<String>,
+) -> Result<(), AudioSourceError> {
+    download_with_args(kind, id, url, Vec::new()).await
+}
+
+/// extra_args: yt-dlp에 더 넘길 인자
+pub async fn download_with_args(
+    kind: AudioSourceKind,
+    id: &str,
+    url: impl Into<String>,
+    extra_args: Vec<String>,
+) -> Result<(), AudioSourceError> {
+    let mut state = {
+        let mut downloads = DOWNLOADS.lock().unwrap();
+
+        if AudioCache::exists(kind, id)? {
+            return Ok(());
+        }
+
+        match downloads.get(&(kind, id.to_string())) {
+            Some(state) => state.clone(),
+            None => {
+                let (tx, rx) = watch::channel(DownloadState::Pending);
+
+                downloads.insert((kind, id.to_string()), rx.clone());
+                tokio::spawn(run(kind, id.to_string(), url.into(), extra_args, tx));
+
+                rx
+            }
+        }
+    };
+
+    let state = state
+        .wait_for(|x| *x != DownloadState::Pending)
+        .await
+        .map(|x| x.clone())
+        .unwrap_or(DownloadState::Done);
+
+    match state {
+        DownloadState::Failed(err) => Err(AudioSourceError::DownloadError(err)),
+        _ => Ok(()),
+    }
+}
+
+/// 받고 있는 중이면 다 받을 때까지 기다림
+pub async fn wait(kind: AudioSourceKind, id: &str) {
+    if let Some(mut state) = get(kind, id) {
+        state
+            .wait_for(|x| matches!(x, DownloadState::Done | DownloadState::Failed(_)))
+            .await
+            .ok();
+    }
+}
+
+async fn run(
+    kind: AudioSourceKind,
+    id: String,
+    url: String,
+    extra_args: Vec<String>,
+    tx: watch::Sender<DownloadState>,
+) {
+    let download_path = AudioCache::download_path(kind, &id);
+
+    if kind.provider().has_subtitles() {
+        tokio::spawn(lyrics::download(kind, id.clone(), url.clone()));
+    }
+
+    let r = match spawn(&url, &download_path, &extra_args, &tx).await {
+        Ok(()) => fs::rename(&download_path, AudioCache::path(kind, &id))
+            .await
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err),
+    };
+
+    if let Err(err) = &r {
+        log::error!("failed to download {url}: {err}");
+
+        fs::remove_file(&download_path).await.ok();
+    }
+
+    // 파일 이름을 바꾼 다음에 지워야 exists와 겹치지 않음
+    DOWNLOADS.lock().unwrap().remove(&(kind, id));
+
+    tx.send_replace(match r {
+        Ok(()) => DownloadState::Done,
+        Err(err) => DownloadState::Failed(err),
+    });
+}
+
+async fn spawn(
+    url: &str,
+    download_path: &str,
+    extra_args: &[String],
+    tx: &watch::Sender<DownloadState>,
+) -> Result<(), String> {
+    let mut child = Command::new(YTDL)
+        .args(["--format", FORMAT])
+        .args(extra_args)
+        .args(["--output", download_path])
+        .args(["--no-part", "--no-progress", "--quiet", "--no-playlist"])
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = tokio::spawn(async move {
+        let mut buf = String::new();
+        stderr.read_to_string(&mut buf).await.ok();
+        buf
+    });
+
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status.map_err(|err| err.to_string())?,
+
+            _ = sleep(POLL_INTERVAL), if *tx.borrow() == DownloadState::Pending => {
+                let len = fs::metadata(download_path).await.map(|x| x.len()).unwrap_or(0);
+
+                if len > 0 {
+                    tx.send_replace(DownloadState::Downloading);
+                }
+            }
+        }
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(stderr.await.unwrap_or_default().trim().to_string())
+    }
+}
+
+/// 받고 있는 중인 음악을 재생할 때 사용함
+///
+/// 다 받았으면 None
+pub fn get_source(kind: AudioSourceKind, id: &str) -> Option<Input> {
+    let state = get(kind, id)?;
+
+    Some(Input::Lazy(Box::new(ProgressiveFile {
+        kind,
+        id: id.to_string(),
+        state,
+    })))
+}
+
+struct ProgressiveFile {
+    kind: AudioSourceKind,
+    id: String,
+    state: watch::Receiver<DownloadState>,
+}
+
+#[async_trait::async_trait]
+impl Compose for ProgressiveFile {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        // 그 사이에 다 받아서 이름이 바뀌었을 수 있음
+        let file = match File::open(AudioCache::download_path(self.kind, &self.id)).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                File::open(AudioCache::path(self.kind, &self.id))
+                    .await
+                    .map_err(|err| AudioStreamError::Fail(Box::new(err)))?
+            }
+            Err(err) => return Err(AudioStreamError::Fail(Box::new(err))),
+        };
+
+        let reader = ProgressiveReader {
+            file,
+            state: self.state.clone(),
+            sleep: None,
+        };
+
+        Ok(AudioStream {
+            input: Box::new(AsyncAdapterStream::new(Box::new(reader), 64 * 1024)),
+            hint: None,
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+struct ProgressiveReader {
+    file: File,
+    state: watch::Receiver<DownloadState>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl AsyncRead for ProgressiveReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(sleep) = self.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                self.sleep = None;
+            }
+
+            // 읽기 전에 확인해야 다 받은 직후에 쓰인 내용을 놓치지 않음
+            let state = self.state.borrow().clone();
+
+            let filled = buf.filled().len();
+
+            ready!(Pin::new(&mut self.file).poll_read(cx, buf))?;
+
+            if buf.filled().len() > filled {
+                return Poll::Ready(Ok(()));
+            }
+
+            match state {
+                DownloadState::Done => return Poll::Ready(Ok(())),
+                DownloadState::Failed(err) => return Poll::Ready(Err(io::Error::other(err))),
+                DownloadState::Pending | DownloadState::Downloading => {
+                    self.sleep = Some(Box::pin(sleep(POLL_INTERVAL)));
+                }
+            }
+        }
+    }
+}
+
+impl AsyncSeek for ProgressiveReader {
+    fn start_seek(self: Pin<&mut Self>, _position: io::SeekFrom) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Err(io::ErrorKind::Unsupported.into()))
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncMediaSource for ProgressiveReader {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    async fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io,
+    path::Path,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+use symphonia::core::{
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::{MetadataOptions, MetadataRevision, StandardTagKey},
+    probe::Hint,
+};
+use tokio::{fs, io::AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::audio::{cache::AudioCache, AudioMetadata, AudioSourceError};
+
+use super::{Credentials, Provider};
+
+/// 다시 재생할 때 사용하는 주소. 뒤에 파일 내용의 sha256이 붙음
+const URL_PREFIX: &str = "attachment:";
+
+/// 디스코드에 첨부된 파일의 주소
+const CDN_HOSTS: [&str; 2] = ["cdn.discordapp.com", "media.discordapp.net"];
+
+const MAX_SIZE: usize = 100 * 1024 * 1024;
+
+/// 처음 재생할 때는 태그에 제목이 없으면 파일 이름을 제목으로 사용함
+///
+/// 파일 이름은 캐시에 남지 않기 때문에 `parse_id`에서 받을 때 정보를 만들어 두고
+/// `get_metadata`에서 꺼냄
+static METADATA: LazyLock<Mutex<HashMap<String, AudioMetadata>>> = LazyLock::new(Default::default);
+
+const METADATA_MAX_LEN: usize = 64;
+
+/// 디스코드에 첨부된 음악 파일
+///
+/// 파일 내용의 sha256을 id로 캐시하고 `attachment:{id}`로 다시 재생함.
+/// 캐시에서 지워지면 다시 재생할 수 없음
+pub struct Attachment;
+
+#[async_trait::async_trait]
+impl Provider for Attachment {
+    fn id(&self) -> &'static str {
+        "attachment"
+    }
+
+    fn name(&self) -> &'static str {
+        "첨부 파일"
+    }
+
+    fn color(&self) -> u32 {
+        0x5865F2
+    }
+
+    fn is_match(&self, url: &str) -> bool {
+        url.strip_prefix(URL_PREFIX).is_some_and(is_valid_id) || is_cdn_url(url)
+    }
+
+    fn url(&self, id: &str) -> Option<String> {
+        Some(format!("{URL_PREFIX}{id}"))
+    }
+
+    /// 첨부된 파일의 주소면 받아서 캐시함
+    async fn parse_id(
+        &self,
+        _credentials: &Credentials,
+        url: &str,
+    ) -> Result<String, AudioSourceError> {
+        match url.strip_prefix(URL_PREFIX) {
+            Some(id) if is_valid_id(id) => Ok(id.to_string()),
+            Some(_) => Err(AudioSourceError::InvalidUrl(url.to_string())),
+            None => self.save(url).await,
+        }
+    }
+
+    /// 태그에 제목이 없으면 기록된 정보로 대신함
+    async fn get_metadata(
+        &self,
+        _credentials: &Credentials,
+        id: &str,
+        _url: &str,
+    ) -> Result<AudioMetadata, AudioSourceError> {
+        if let Some(audio_metadata) = METADATA.lock().unwrap().remove(id) {
+            return Ok(audio_metadata);
+        }
+
+        let path = AudioCache::path(self.kind(), id);
+        let probed = tokio::task::spawn_blocking(move || probe(&path, None))
+            .await
+            .map_err(io::Error::other)??;
+
+        match probed.title {
+            Some(title) => Ok(self.to_metadata(id, title, probed.artist, probed.duration)),
+            None => Err(AudioSourceError::NoMetadata),
+        }
+    }
+
+    /// `parse_id`에서 이미 캐시했음
+    async fn download(&self, id: &str, _url: &str) -> Result<(), AudioSourceError> {
+        if AudioCache::exists(self.kind(), id)? {
+            Ok(())
+        } else {
+            Err(AudioSourceError::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                "캐시에서 지워진 파일이에요. 다시 첨부해 주세요",
+            )))
+        }
+    }
+}
+
+/// 크기를 알려주지 않는 응답도 있어서 받으면서 크기를 확인하고 sha256을 계산함
+///
+/// returns sha256
+async fn write(mut resp: reqwest::Response, path: &str) -> Result<String, AudioSourceError> {
+    let mut file = fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut len = 0;
+
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .map_err(|err| AudioSourceError::DownloadError(err.to_string()))?
+    {
+        len += chunk.len();
+
+        if len > MAX_SIZE {
+            return Err(AudioSourceError::FileTooLarge(MAX_SIZE));
+        }
+
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+
+    file.flush().await?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+impl Attachment {
+    async fn save(&self, url: &str) -> Result<String, AudioSourceError> {
+        let resp = reqwest::get(url)
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| AudioSourceError::DownloadError(err.to_string()))?;
+
+        if resp.content_length().unwrap_or(0) as usize > MAX_SIZE {
+            return Err(AudioSourceError::FileTooLarge(MAX_SIZE));
+        }
+
+        // 동시에 같은 파일을 받아도 겹치지 않도록 요청마다 다른 파일에 받음
+        let download_path = AudioCache::download_path(self.kind(), &Uuid::new_v4().to_string());
+
+        let id = match write(resp, &download_path).await {
+            Ok(id) => id,
+            Err(err) => {
+                fs::remove_file(&download_path).await.ok();
+                return Err(err);
+            }
+        };
+
+        let path = AudioCache::path(self.kind(), &id);
+
+        // 다른 서버에서 같은 파일을 동시에 첨부해도 받는 중인 파일을 읽지 않도록 이름을 바꿈
+        if AudioCache::exists(self.kind(), &id)? {
+            fs::remove_file(&download_path).await?;
+        } else {
+            fs::rename(&download_path, &path).await?;
+        }
+
+        let filename = filename(url);
+        let extension = Path::new(&filename)
+            .extension()
+            .map(|x| x.to_string_lossy().into_owned());
+
+        let probed = match tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || probe(&path, extension.as_deref())
+        })
+        .await
+        .map_err(io::Error::other)?
+        {
+            Ok(probed) => probed,
+            Err(err) => {
+                // 재생할 수 없는 파일은 캐시하지 않음
+                fs::remove_file(&path).await.ok();
+                return Err(err);
+            }
+        };
+
+        let title = probed.title.unwrap_or_else(|| {
+            Path::new(&filename)
+                .file_stem()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or(filename.clone())
+        });
+
+        let audio_metadata = self.to_metadata(&id, title, probed.artist, probed.duration);
+
+        let mut metadata = METADATA.lock().unwrap();
+
+        if metadata.len() >= METADATA_MAX_LEN {
+            metadata.clear();
+        }
+
+        metadata.insert(id.clone(), audio_metadata);
+
+        Ok(id)
+    }
+
+    fn to_metadata(
+        &self,
+        id: &str,
+        title: String,
+        artist: Option<String>,
+        duration: Option<Duration>,
+    ) -> AudioMetadata {
+        AudioMetadata {
+            id: id.to_string(),
+            title,
+            url: format!("{URL_PREFIX}{id}"),
+            thumbnail_url: None,
+            uploaded_by: artist.unwrap_or_else(|| self.name().to_string()),
+
+            duration,
+            _kind: self.kind(),
+        }
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|x| x.is_ascii_hexdigit())
+}
+
+fn is_cdn_url(url: &str) -> bool {
+    url.parse::<http::Uri>()
+        .map(|uri| {
+            uri.scheme_str() == Some("https")
+                && uri.host().is_some_and(|host| CDN_HOSTS.contains(&host))
+                && uri.path().starts_with("/attachments/")
+        })
+        .unwrap_or(false)
+}
+
+/// 주소의 마지막 경로
+fn filename(url: &str) -> String {
+    url.parse::<http::Uri>()
+        .ok()
+        .and_then(|uri| uri.path().rsplit('/').next().map(str::to_string))
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| "attachment".to_string())
+}
+
+struct Probed {
+    title: Option<String>,
+    artist: Option<String>,
+    duration: Option<Duration>,
+}
+
+/// 재생할 수 있는 파일인지 확인하고 태그와 재생 시간을 읽음
+fn probe(path: &str, extension: Option<&str>) -> Result<Probed, AudioSourceError> {
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let mut probed = symphonia::default::get_probe().format(
+        &hint,
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    let track = probed
+        .format
+        .default_track()
+        .ok_or(symphonia::core::errors::Error::Unsupported("no track"))?;
+
+    let duration = track
+        .codec_params
+        .time_base
+        .zip(track.codec_params.n_frames)
+        .map(|(time_base, n_frames)| {
+            let time = time_base.calc_time(n_frames);
+            Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+        });
+
+    // 컨테이너의 태그가 없으면 파일 앞에 붙은 ID3 태그를 읽음
+    let mut find_tag = |key: StandardTagKey| {
+        let get = |revision: &MetadataRevision| {
+            revision
+                .tags()
+                .iter()
+                .find(|x| x.std_key == Some(key))
+                .map(|x| x.value.to_string())
+                .filter(|x| !x.trim().is_empty())
+        };
+
+        probed
+            .format
+            .metadata()
+            .current()
+            .and_then(get)
+            .or_else(|| probed.metadata.get()?.current().and_then(get))
+    };
+
+    Ok(Probed {
+        title: find_tag(StandardTagKey::TrackTitle),
+        artist: find_tag(StandardTagKey::Artist),
+        duration,
+    })
+}
+
+#[test]
+fn test_is_match() {
+    let id = "a".repeat(64);
+
+    assert!(Attachment.is_match(&format!("attachment:{id}")));
+    assert!(
+        Attachment.is_match("https://cdn.discordapp.com/attachments/1/2/song.mp3?ex=1&is=2&hm=3")
+    );
+    assert!(!Attachment.is_match("attachment:../../etc/passwd"));
+    assert!(!Attachment.is_match("https://cdn.discordapp.com/avatars/1/2.png"));
+}
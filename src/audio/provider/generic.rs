@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+use youtube_dl::{SingleVideo, YoutubeDl, YoutubeDlOutput};
+
+use crate::audio::{progressive, proxy, AudioMetadata, AudioSourceError, YTDL};
+
+use super::{Credentials, Provider};
+
+/// 주소마다 정보를 가져오는 데 몇 초씩 걸리기 때문에
+/// `parse_id`에서 가져온 정보를 `get_metadata`에서 다시 사용함
+///
+/// 받지 못해서 `get_metadata`까지 가지 않은 정보가 쌓이지 않도록 가득 차면 비움
+static METADATA: LazyLock<Mutex<HashMap<String, AudioMetadata>>> = LazyLock::new(Default::default);
+
+const METADATA_MAX_LEN: usize = 64;
+
+/// 재생 기록 버튼의 custom_id(100자)에 들어가도록 id의 길이를 제한함
+const MAX_ID_LEN: usize = 64;
+
+/// 유튜브, 사운드클라우드가 아닌 주소를 yt-dlp가 지원하는 곳에서 가져옴
+///
+/// id만으로 주소를 만들 수 없어서 기록된 주소로 다시 재생함
+pub struct Generic;
+
+#[async_trait::async_trait]
+impl Provider for Generic {
+    fn id(&self) -> &'static str {
+        "ytdlp"
+    }
+
+    fn name(&self) -> &'static str {
+        "기타"
+    }
+
+    fn color(&self) -> u32 {
+        0x808080
+    }
+
+    /// 봇이 돌아가는 곳의 내부 주소에 접근하지 못하도록 공개된 주소만 받음
+    ///
+    /// 리다이렉트나 DNS로 내부 주소를 가리킬 수도 있어서 yt-dlp는 [`proxy`]를 거쳐서 접근함
+    fn is_match(&self, url: &str) -> bool {
+        url.parse::<http::Uri>()
+            .map(|uri| {
+                matches!(uri.scheme_str(), Some("http" | "https"))
+                    && uri.host().is_some_and(proxy::is_public_host)
+            })
+            .unwrap_or(false)
+    }
+
+    async fn parse_id(
+        &self,
+        _credentials: &Credentials,
+        url: &str,
+    ) -> Result<String, AudioSourceError> {
+        let audio_metadata = get(url).await?;
+        let id = audio_metadata.id.clone();
+
+        let mut metadata = METADATA.lock().unwrap();
+
+        if metadata.len() >= METADATA_MAX_LEN {
+            metadata.clear();
+        }
+
+        metadata.insert(url.to_string(), audio_metadata);
+
+        Ok(id)
+    }
+
+    async fn get_metadata(
+        &self,
+        _credentials: &Credentials,
+        _id: &str,
+        url: &str,
+    ) -> Result<AudioMetadata, AudioSourceError> {
+        let audio_metadata = METADATA.lock().unwrap().remove(url);
+
+        match audio_metadata {
+            Some(audio_metadata) => Ok(audio_metadata),
+            None => get(url).await,
+        }
+    }
+
+    async fn download(&self, id: &str, url: &str) -> Result<(), AudioSourceError> {
+        progressive::download_with_args(self.kind(), id, url, proxy::ytdlp_args().await?).await
+    }
+}
+
+async fn get(url: &str) -> Result<AudioMetadata, AudioSourceError> {
+    let mut ytdl = YoutubeDl::new(url);
+
+    ytdl.youtube_dl_path(YTDL).extra_arg("--no-playlist");
+
+    for arg in proxy::ytdlp_args().await? {
+        ytdl.extra_arg(arg);
+    }
+
+    let output = ytdl.run_async().await?;
+
+    match output {
+        YoutubeDlOutput::SingleVideo(video) => Ok(into_metadata(*video, url)),
+        YoutubeDlOutput::Playlist(_) => Err(AudioSourceError::MustSingleVideo),
+    }
+}
+
+fn into_metadata(video: SingleVideo, url: &str) -> AudioMetadata {
+    let extractor = video.extractor_key.as_deref().unwrap_or("generic");
+
+    AudioMetadata {
+        id: parse_id(extractor, &video.id),
+        title: video.title.unwrap_or_else(|| video.id.clone()),
+        url: video.webpage_url.unwrap_or_else(|| url.to_string()),
+        thumbnail_url: video.thumbnail,
+        uploaded_by: video
+            .uploader
+            .or(video.channel)
+            .unwrap_or_else(|| extractor.to_string()),
+
+        duration: video
+            .duration
+            .and_then(|x| x.as_f64())
+            .filter(|x| x.is_finite() && *x >= 0.0)
+            .map(Duration::from_secs_f64),
+        _kind: Generic.kind(),
+    }
+}
+
+/// 다른 곳의 id와 겹치지 않도록 extractor를 붙이고
+/// 캐시 파일 이름으로 사용할 수 없는 문자를 바꿈
+///
+/// 너무 길면 뒷부분을 해시로 바꿈
+fn parse_id(extractor: &str, id: &str) -> String {
+    let id = format!("{extractor}-{id}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if id.len() <= MAX_ID_LEN {
+        return id;
+    }
+
+    let hash = hex::encode(Sha256::digest(id.as_bytes()));
+
+    format!("{}-{}", &id[..MAX_ID_LEN - 17], &hash[..16])
+}
+
+#[test]
+fn test_is_match() {
+    assert!(Generic.is_match("https://vimeo.com/123"));
+    assert!(Generic.is_match("http://1.1.1.1/a.mp3"));
+    assert!(!Generic.is_match("ftp://vimeo.com/123"));
+    assert!(!Generic.is_match("http://localhost:8080/"));
+    assert!(!Generic.is_match("http://api.localhost/"));
+    assert!(!Generic.is_match("http://127.0.0.1/"));
+    assert!(!Generic.is_match("http://10.0.0.1/"));
+    assert!(!Generic.is_match("http://172.16.0.1/"));
+    assert!(!Generic.is_match("http://192.168.0.1/"));
+    assert!(!Generic.is_match("http://169.254.169.254/latest/meta-data/"));
+    assert!(!Generic.is_match("http://0.0.0.0/"));
+    assert!(!Generic.is_match("http://[::1]/"));
+    assert!(!Generic.is_match("http://[::ffff:127.0.0.1]/"));
+    assert!(!Generic.is_match("http://[fd00::1]/"));
+}
+
+#[test]
+fn test_parse_id() {
+    assert_eq!(parse_id("Bandcamp", "1234"), "Bandcamp-1234");
+    assert_eq!(parse_id("Vimeo", "../a b"), "Vimeo-___a_b");
+
+    let long = parse_id("Generic", &"a".repeat(200));
+    assert_eq!(long.len(), MAX_ID_LEN);
+    assert_ne!(long, parse_id("Generic", &"a".repeat(201)));
+}
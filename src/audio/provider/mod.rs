@@ -0,0 +1,148 @@
+//! 음악을 가져오는 곳
+//!
+//! 새로운 곳에서 음악을 가져오려면 `Provider`를 구현하고 `PROVIDERS`에 추가하면 됨
+
+mod attachment;
+mod generic;
+mod soundcloud;
+mod youtube;
+
+pub use attachment::Attachment;
+pub use generic::Generic;
+pub use soundcloud::SoundCloud;
+pub use youtube::YouTube;
+
+use crate::store::History;
+
+use super::{progressive, AudioMetadata, AudioPlaylist, AudioSourceError, AudioSourceKind};
+
+/// 음악을 가져올 때 필요한 API 키
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub youtube_api_key: String,
+    pub soundcloud_client_id: String,
+}
+
+#[async_trait::async_trait]
+pub trait Provider: Send + Sync {
+    /// history, track_queue, audio_cache의 kind와 캐시 디렉터리 이름으로 사용함
+    ///
+    /// 기록이 남아 있기 때문에 바꾸면 안 됨
+    fn id(&self) -> &'static str;
+
+    /// 사용자에게 보여줄 이름
+    fn name(&self) -> &'static str;
+
+    /// history channel에 보내는 embed의 색
+    fn color(&self) -> u32;
+
+    fn kind(&self) -> AudioSourceKind {
+        AudioSourceKind(self.id())
+    }
+
+    fn is_match(&self, url: &str) -> bool;
+
+    fn is_playlist(&self, _url: &str) -> bool {
+        false
+    }
+
+    fn normalize_url(&self, url: String) -> String {
+        url
+    }
+
+    /// 기록된 id로 다시 재생할 때 사용할 주소
+    ///
+    /// id로 주소를 만들 수 없으면 기록된 주소를 사용함
+    fn url(&self, _id: &str) -> Option<String> {
+        None
+    }
+
+    /// 주소에서 음악의 id를 꺼냄
+    async fn parse_id(
+        &self,
+        credentials: &Credentials,
+        url: &str,
+    ) -> Result<String, AudioSourceError>;
+
+    async fn get_metadata(
+        &self,
+        credentials: &Credentials,
+        id: &str,
+        url: &str,
+    ) -> Result<AudioMetadata, AudioSourceError>;
+
+    /// 재생목록의 음악을 limit개까지 가져옴
+    async fn get_playlist(
+        &self,
+        _credentials: &Credentials,
+        _url: &str,
+        _limit: usize,
+    ) -> Result<AudioPlaylist, AudioSourceError> {
+        Err(AudioSourceError::PlaylistNotSupported)
+    }
+
+    /// 음악을 받을 때 yt-dlp로 자막을 같이 받아서 `/lyrics`에 사용함
+    fn has_subtitles(&self) -> bool {
+        false
+    }
+
+    /// 캐시 디렉터리에 받기 시작하고 받은 내용이 생길 때까지 기다림
+    async fn download(&self, id: &str, url: &str) -> Result<(), AudioSourceError> {
+        progressive::download(self.kind(), id, url).await
+    }
+
+    /// API로 정보를 가져올 수 없을 때 기록된 정보로 대신함
+    fn metadata_from_history(&self, history: History) -> AudioMetadata {
+        AudioMetadata {
+            url: self.url(&history.uid).or(history.url).unwrap_or_default(),
+            id: history.uid,
+            title: history.title,
+            thumbnail_url: None,
+            uploaded_by: history.channel,
+            duration: None,
+            _kind: self.kind(),
+        }
+    }
+}
+
+/// 주소를 확인하는 순서
+///
+/// `Generic`은 모든 주소와 일치하기 때문에 마지막에 있어야 함
+static PROVIDERS: &[&dyn Provider] = &[&YouTube, &SoundCloud, &Attachment, &Generic];
+
+pub fn all() -> impl Iterator<Item = &'static dyn Provider> {
+    PROVIDERS.iter().copied()
+}
+
+pub fn get(id: &str) -> Option<&'static dyn Provider> {
+    all().find(|x| x.id() == id)
+}
+
+/// 주소를 재생할 수 있는 곳
+pub fn find(url: &str) -> Option<&'static dyn Provider> {
+    all().find(|x| x.is_match(url) || x.is_playlist(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_provider() {
+        let cases = [
+            ("https://youtu.be/dQw4w9WgXcQ", Some("youtube")),
+            (
+                "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+                Some("youtube"),
+            ),
+            ("https://soundcloud.com/user/track", Some("soundcloud")),
+            ("https://bandcamp.com/track/a", Some("ytdlp")),
+            ("ftp://example.com/a.mp3", None),
+            ("검색어", None),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(find(url).map(|x| x.id()), expected, "{url}");
+        }
+    }
+}
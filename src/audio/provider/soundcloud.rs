@@ -0,0 +1,76 @@
+use crate::audio::{scdl, AudioMetadata, AudioPlaylist, AudioSourceError};
+
+use super::{Credentials, Provider};
+
+const API_TRACK_URL: &str = "https://api-v2.soundcloud.com/tracks/";
+
+pub struct SoundCloud;
+
+#[async_trait::async_trait]
+impl Provider for SoundCloud {
+    fn id(&self) -> &'static str {
+        "soundcloud"
+    }
+
+    fn name(&self) -> &'static str {
+        "사운드클라우드"
+    }
+
+    fn color(&self) -> u32 {
+        0xF26F23
+    }
+
+    fn is_match(&self, url: &str) -> bool {
+        scdl::is_soundcloud_url(url) || url.starts_with(API_TRACK_URL)
+    }
+
+    fn is_playlist(&self, url: &str) -> bool {
+        scdl::is_soundcloud_set_url(url)
+    }
+
+    fn url(&self, id: &str) -> Option<String> {
+        Some(format!("{API_TRACK_URL}{id}"))
+    }
+
+    /// 기록된 주소가 아니면 API로 id를 가져옴
+    async fn parse_id(
+        &self,
+        credentials: &Credentials,
+        url: &str,
+    ) -> Result<String, AudioSourceError> {
+        if let Some(id) = url.strip_prefix(API_TRACK_URL) {
+            return Ok(id.to_string());
+        }
+
+        let track = scdl::get_track(&credentials.soundcloud_client_id, url).await?;
+
+        Ok(track.id.to_string())
+    }
+
+    async fn get_metadata(
+        &self,
+        credentials: &Credentials,
+        _id: &str,
+        url: &str,
+    ) -> Result<AudioMetadata, AudioSourceError> {
+        let track = scdl::get_track(&credentials.soundcloud_client_id, url).await?;
+
+        Ok(track.into())
+    }
+
+    async fn get_playlist(
+        &self,
+        credentials: &Credentials,
+        url: &str,
+        limit: usize,
+    ) -> Result<AudioPlaylist, AudioSourceError> {
+        let mut playlist: AudioPlaylist =
+            scdl::get_playlist(&credentials.soundcloud_client_id, url)
+                .await?
+                .into();
+
+        playlist.items.truncate(limit);
+
+        Ok(playlist)
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use youtube_dl::{SearchOptions, YoutubeDl, YoutubeDlOutput};
+
+use crate::audio::{ytdl, AudioMetadata, AudioPlaylist, AudioSourceError, YTDL};
+
+use super::{Credentials, Provider};
+
+pub struct YouTube;
+
+impl YouTube {
+    /// yt-dlp로 검색함. API 할당량을 사용하지 않지만 API보다 느림
+    ///
+    /// 썸네일은 가져오지 않음
+    pub async fn search(
+        keyword: &str,
+        limit: usize,
+    ) -> Result<Vec<AudioMetadata>, AudioSourceError> {
+        let output = YoutubeDl::search_for(&SearchOptions::youtube(keyword).with_count(limit))
+            .youtube_dl_path(YTDL)
+            .flat_playlist(true)
+            .run_async()
+            .await?;
+
+        let entries = match output {
+            YoutubeDlOutput::Playlist(playlist) => playlist.entries.unwrap_or_default(),
+            YoutubeDlOutput::SingleVideo(video) => vec![*video],
+        };
+
+        let audio_metadatas = entries
+            .into_iter()
+            .map(|video| AudioMetadata {
+                url: format!("https://youtu.be/{}", video.id),
+                title: video.title.unwrap_or_else(|| video.id.clone()),
+                id: video.id,
+                thumbnail_url: None,
+                uploaded_by: video.channel.or(video.uploader).unwrap_or_default(),
+                duration: video
+                    .duration
+                    .and_then(|x| x.as_f64())
+                    .filter(|x| x.is_finite() && *x >= 0.0)
+                    .map(Duration::from_secs_f64),
+                _kind: YouTube.kind(),
+            })
+            .collect();
+
+        Ok(audio_metadatas)
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for YouTube {
+    fn id(&self) -> &'static str {
+        "youtube"
+    }
+
+    fn name(&self) -> &'static str {
+        "유튜브"
+    }
+
+    fn color(&self) -> u32 {
+        0xFF0000
+    }
+
+    fn is_match(&self, url: &str) -> bool {
+        ytdl::is_youtube_url(url)
+    }
+
+    fn is_playlist(&self, url: &str) -> bool {
+        ytdl::is_youtube_playlist_url(url)
+    }
+
+    fn normalize_url(&self, url: String) -> String {
+        if url.contains("youtube.com/shorts/") {
+            url.replacen("shorts", "watch", 1)
+        } else {
+            url
+        }
+    }
+
+    fn url(&self, id: &str) -> Option<String> {
+        Some(format!("https://youtu.be/{id}"))
+    }
+
+    fn has_subtitles(&self) -> bool {
+        true
+    }
+
+    async fn parse_id(
+        &self,
+        _credentials: &Credentials,
+        url: &str,
+    ) -> Result<String, AudioSourceError> {
+        let uri = url
+            .parse()
+            .map_err(|_| AudioSourceError::InvalidUrl(url.to_string()))?;
+
+        Ok(ytdl::parse_vid(uri))
+    }
+
+    async fn get_metadata(
+        &self,
+        credentials: &Credentials,
+        id: &str,
+        _url: &str,
+    ) -> Result<AudioMetadata, AudioSourceError> {
+        Ok(ytdl::get(&credentials.youtube_api_key, id).await?)
+    }
+
+    async fn get_playlist(
+        &self,
+        credentials: &Credentials,
+        url: &str,
+        limit: usize,
+    ) -> Result<AudioPlaylist, AudioSourceError> {
+        let playlist_id = url
+            .parse()
+            .ok()
+            .and_then(ytdl::parse_playlist_id)
+            .unwrap_or_default();
+
+        Ok(ytdl::get_playlist(&credentials.youtube_api_key, playlist_id, limit).await?)
+    }
+
+    /// -로 시작하는 id는 yt-dlp가 옵션으로 읽어서 주소로 받음
+    async fn download(&self, id: &str, _url: &str) -> Result<(), AudioSourceError> {
+        crate::audio::progressive::download(self.kind(), id, format!("https://youtu.be/{id}")).await
+    }
+}
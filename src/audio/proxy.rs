@@ -0,0 +1,279 @@
+//! yt-dlp가 봇이 돌아가는 곳의 내부 주소에 접근하지 못하도록 중계하는 HTTP 프록시
+//!
+//! 리다이렉트를 따라가거나 DNS를 다시 찾아도 모든 연결이 이 프록시를 거치기 때문에
+//! 연결할 때마다 찾은 주소를 확인하고 확인한 주소로만 연결함
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::OnceCell,
+};
+
+/// 요청 줄과 헤더의 최대 크기
+const MAX_HEAD_LEN: usize = 16 * 1024;
+
+static PROXY_URL: OnceCell<String> = OnceCell::const_new();
+
+/// yt-dlp의 `--proxy`에 넘길 주소. 처음 사용할 때 프록시를 띄움
+pub async fn url() -> io::Result<String> {
+    PROXY_URL
+        .get_or_try_init(|| async {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+            let url = format!("http://{}", listener.local_addr()?);
+
+            tokio::spawn(serve(listener));
+
+            Ok(url)
+        })
+        .await
+        .cloned()
+}
+
+/// yt-dlp에 넘길 인자
+pub async fn ytdlp_args() -> io::Result<Vec<String>> {
+    Ok(vec!["--proxy".to_string(), url().await?])
+}
+
+async fn serve(listener: TcpListener) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                log::error!("failed to accept proxy connection: {err}");
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream).await {
+                log::debug!("proxy connection closed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle(mut client: TcpStream) -> io::Result<()> {
+    let mut buf = Vec::new();
+
+    let head_len = loop {
+        if let Some(x) = buf.windows(4).position(|x| x == b"\r\n\r\n") {
+            break x + 4;
+        }
+
+        if buf.len() > MAX_HEAD_LEN {
+            return respond(&mut client, "431 Request Header Fields Too Large").await;
+        }
+
+        let mut chunk = [0; 4096];
+        let n = client.read(&mut chunk).await?;
+
+        if n == 0 {
+            return Ok(());
+        }
+
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_len]).into_owned();
+    let (request_line, headers) = head.split_once("\r\n").unwrap_or((&head, ""));
+
+    let Some(Request {
+        method,
+        host,
+        port,
+        path,
+        version,
+    }) = parse_request_line(request_line)
+    else {
+        return respond(&mut client, "400 Bad Request").await;
+    };
+
+    let Some(addr) = resolve(&host, port).await else {
+        log::warn!("blocked proxy request to {host}:{port}");
+        return respond(&mut client, "403 Forbidden").await;
+    };
+
+    let mut upstream = match TcpStream::connect(addr).await {
+        Ok(upstream) => upstream,
+        Err(_) => return respond(&mut client, "502 Bad Gateway").await,
+    };
+
+    if method == "CONNECT" {
+        client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await?;
+    } else {
+        // 프록시에 보내는 요청은 전체 주소라서 서버에 보내는 형식으로 바꿈
+        upstream
+            .write_all(format!("{method} {path} {version}\r\n{headers}").as_bytes())
+            .await?;
+    }
+
+    upstream.write_all(&buf[head_len..]).await?;
+
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+
+    Ok(())
+}
+
+async fn respond(client: &mut TcpStream, status: &str) -> io::Result<()> {
+    client
+        .write_all(
+            format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .as_bytes(),
+        )
+        .await
+}
+
+/// 공개된 주소가 아닌 곳이 하나라도 있으면 None
+async fn resolve(host: &str, port: u16) -> Option<SocketAddr> {
+    if !is_public_host(host) {
+        return None;
+    }
+
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let addrs = tokio::net::lookup_host((host, port))
+        .await
+        .ok()?
+        .collect::<Vec<_>>();
+
+    if addrs.iter().all(|x| is_public_ip(x.ip())) {
+        addrs.into_iter().next()
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    method: String,
+    host: String,
+    port: u16,
+    /// CONNECT이면 빈 문자열
+    path: String,
+    version: String,
+}
+
+/// `CONNECT host:port HTTP/1.1` 또는 `GET http://host[:port]/path HTTP/1.1`
+fn parse_request_line(x: &str) -> Option<Request> {
+    let mut xs = x.split(' ');
+
+    let method = xs.next()?.to_string();
+    let target = xs.next()?;
+    let version = xs.next()?.to_string();
+
+    if method == "CONNECT" {
+        let authority = target.parse::<http::uri::Authority>().ok()?;
+
+        return Some(Request {
+            method,
+            host: authority.host().to_string(),
+            port: authority.port_u16()?,
+            path: String::new(),
+            version,
+        });
+    }
+
+    let uri = target.parse::<http::Uri>().ok()?;
+
+    if uri.scheme_str() != Some("http") {
+        return None;
+    }
+
+    Some(Request {
+        method,
+        host: uri.host()?.to_string(),
+        port: uri.port_u16().unwrap_or(80),
+        path: uri
+            .path_and_query()
+            .map(|x| x.as_str())
+            .unwrap_or("/")
+            .to_string(),
+        version,
+    })
+}
+
+pub fn is_public_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    if host.eq_ignore_ascii_case("localhost") || host.to_ascii_lowercase().ends_with(".localhost") {
+        return false;
+    }
+
+    host.parse::<IpAddr>().map_or(true, is_public_ip)
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+#[test]
+fn test_parse_request_line() {
+    let r = parse_request_line("CONNECT vimeo.com:443 HTTP/1.1").unwrap();
+    assert_eq!(
+        (r.host.as_str(), r.port, r.path.as_str()),
+        ("vimeo.com", 443, "")
+    );
+
+    let r = parse_request_line("GET http://1.1.1.1:8080/a?b=c HTTP/1.1").unwrap();
+    assert_eq!(
+        (r.host.as_str(), r.port, r.path.as_str()),
+        ("1.1.1.1", 8080, "/a?b=c")
+    );
+
+    let r = parse_request_line("CONNECT [::1]:443 HTTP/1.1").unwrap();
+    assert!(!is_public_host(&r.host));
+
+    assert!(parse_request_line("GET /a HTTP/1.1").is_none());
+    assert!(parse_request_line("GET file:///etc/passwd HTTP/1.1").is_none());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_resolve() {
+    assert!(resolve("127.0.0.1", 80).await.is_none());
+    assert!(resolve("169.254.169.254", 80).await.is_none());
+    assert!(resolve("[::1]", 443).await.is_none());
+    assert!(resolve("localhost", 80).await.is_none());
+    assert!(resolve("1.1.1.1", 443).await.is_some());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_proxy_blocks_private_address() {
+    let addr = url().await.unwrap().replace("http://", "");
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"CONNECT 169.254.169.254:80 HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut r = String::new();
+    stream.read_to_string(&mut r).await.unwrap();
+
+    assert!(r.starts_with("HTTP/1.1 403"));
+}
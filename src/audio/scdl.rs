@@ -25,6 +25,23 @@ pub struct User {
     pub avatar_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct Playlist {
+    pub id: u64,
+    pub title: String,
+    pub permalink_url: String,
+    pub tracks: Vec<Track>,
+}
+
+/// 세트의 앞쪽 몇 곡만 모든 정보가 있고 나머지는 id만 있음
+#[derive(Debug, Deserialize)]
+struct PartialPlaylist {
+    id: u64,
+    title: String,
+    permalink_url: String,
+    tracks: Vec<serde_json::Value>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("reqwest: {0}")]
@@ -38,6 +55,10 @@ pub fn is_soundcloud_shared_url(x: &str) -> bool {
     x.starts_with("https://on.soundcloud.com/")
 }
 
+pub fn is_soundcloud_set_url(x: &str) -> bool {
+    x.starts_with("https://soundcloud.com/") && x.contains("/sets/")
+}
+
 pub fn is_soundcloud_url(x: &str) -> bool {
     x.starts_with("https://soundcloud.com/") || is_soundcloud_shared_url(x)
 }
@@ -66,15 +87,83 @@ async fn get_track_url_from_shared_url(shared_url: &str) -> Result<String, Error
 }
 
 pub async fn get_track(client_id: &str, track_url: &str) -> Result<Track, Error> {
-    let track_url = if is_soundcloud_shared_url(track_url) {
-        get_track_url_from_shared_url(track_url).await?
+    resolve(client_id, track_url).await
+}
+
+/// 세트의 곡을 순서대로 가져옴
+pub async fn get_playlist(client_id: &str, playlist_url: &str) -> Result<Playlist, Error> {
+    let PartialPlaylist {
+        id,
+        title,
+        permalink_url,
+        tracks,
+    } = resolve(client_id, playlist_url).await?;
+
+    let mut ids = Vec::new();
+    let tracks = tracks
+        .into_iter()
+        .map(|x| match serde_json::from_value::<Track>(x.clone()) {
+            Ok(track) => Ok(track),
+            Err(_) => {
+                let id = x.get("id").and_then(|x| x.as_u64()).unwrap_or_default();
+                ids.push(id);
+                Err(id)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut fetched = Vec::new();
+
+    // 한 번에 50곡까지 가져올 수 있음
+    for ids in ids.chunks(50) {
+        let ids = ids
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let params = [("client_id", client_id), ("ids", &ids)];
+
+        let resp = reqwest::Client::new()
+            .get("https://api-v2.soundcloud.com/tracks")
+            .query(&params)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let buf = resp.bytes().await?;
+        let xs: Vec<Track> =
+            serde_json::from_slice(&buf).map_err(|err| Error::Other(err.to_string()))?;
+
+        fetched.extend(xs);
+    }
+
+    // 비공개이거나 삭제된 곡은 가져오지 못하므로 빠짐
+    let tracks = tracks
+        .into_iter()
+        .filter_map(|x| match x {
+            Ok(track) => Some(track),
+            Err(id) => fetched.iter().find(|x| x.id == id).cloned(),
+        })
+        .collect();
+
+    Ok(Playlist {
+        id,
+        title,
+        permalink_url,
+        tracks,
+    })
+}
+
+async fn resolve<T: serde::de::DeserializeOwned>(client_id: &str, url: &str) -> Result<T, Error> {
+    let url = if is_soundcloud_shared_url(url) {
+        get_track_url_from_shared_url(url).await?
     } else {
-        track_url.to_string()
+        url.to_string()
     };
 
-    // println!("{}", track_url);
+    // println!("{}", url);
 
-    let params = [("client_id", client_id), ("url", &track_url)];
+    let params = [("client_id", client_id), ("url", &url)];
 
     let resp = reqwest::Client::new()
         .get("https://api-v2.soundcloud.com/resolve")
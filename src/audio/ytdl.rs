@@ -3,7 +3,7 @@ use std::{collections::HashMap, fmt::Display};
 use http::Uri;
 use serde::Deserialize;
 
-use crate::audio::AudioMetadata;
+use crate::audio::{AudioMetadata, AudioPlaylist};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -51,6 +51,47 @@ pub struct VideoItem {
     pub content_details: Option<ContentDetail>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistResult {
+    pub items: Option<Vec<PlaylistItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItem {
+    pub snippet: Option<Snippet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemsResult {
+    pub next_page_token: Option<String>,
+    pub items: Option<Vec<PlaylistItemsItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemsItem {
+    pub snippet: Option<PlaylistItemSnippet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemSnippet {
+    pub title: Option<String>,
+    pub thumbnails: Option<HashMap<String, Thumbnail>>,
+    /// 삭제되었거나 비공개인 영상은 없음
+    pub video_owner_channel_title: Option<String>,
+    pub resource_id: Option<ResourceId>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceId {
+    pub video_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchItemId {
@@ -126,6 +167,16 @@ struct YouTuneErrorWrapper {
     pub error: Error,
 }
 
+pub fn is_youtube_playlist_url(x: &str) -> bool {
+    if x.starts_with("https://www.youtube.com/playlist")
+        || x.starts_with("https://music.youtube.com/playlist")
+    {
+        return true;
+    }
+
+    is_youtube_url(x) && x.parse::<Uri>().ok().and_then(parse_playlist_id).is_some()
+}
+
 pub fn is_youtube_url(x: &str) -> bool {
     x.starts_with("https://music.youtube.com/watch")
         || x.starts_with("https://www.youtube.com/watch")
@@ -173,7 +224,7 @@ pub async fn get(
 
     let item = a.items.unwrap_or_default().into_iter().next();
 
-    match item.map(|item| AudioMetadata::try_from(item)) {
+    match item.map(AudioMetadata::try_from) {
         Some(Ok(r)) => Ok(r),
         Some(Err(_)) => Err(Error {
             code: 404,
@@ -291,6 +342,117 @@ async fn test_search() {
     println!("{xs:#?}");
 }
 
+/// 자동으로 만들어지는 믹스(RD로 시작)는 API로 가져올 수 없어서 재생목록으로 취급하지 않음
+pub fn parse_playlist_id(uri: Uri) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Query {
+        list: Option<String>,
+    }
+
+    let Query { list } = serde_qs::from_str(uri.query().unwrap_or_default()).ok()?;
+
+    list.filter(|x| !x.is_empty() && !x.starts_with("RD"))
+}
+
+/// 필드가 모두 Option이라서 에러 응답도 파싱되기 때문에 에러인지 먼저 확인함
+fn parse<T: serde::de::DeserializeOwned>(buf: &[u8]) -> Result<T, Error> {
+    if let Ok(err) = Error::from_slice(buf) {
+        return Err(err);
+    }
+
+    serde_json::from_slice(buf).map_err(|err| Error {
+        code: 0,
+        message: err.to_string(),
+    })
+}
+
+/// 재생목록의 영상을 순서대로 가져옴
+///
+/// 삭제되었거나 비공개인 영상은 건너뜀
+pub async fn get_playlist(
+    youtube_api_key: impl AsRef<str>,
+    playlist_id: impl AsRef<str>,
+    limit: usize,
+) -> Result<AudioPlaylist, Error> {
+    let youtube_api_key = youtube_api_key.as_ref();
+    let playlist_id = playlist_id.as_ref();
+
+    let title = {
+        let params = [
+            ("part", "snippet"),
+            ("key", youtube_api_key),
+            ("id", playlist_id),
+        ];
+
+        let buf = reqwest::Client::new()
+            .get("https://www.googleapis.com/youtube/v3/playlists")
+            .query(&params)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+
+        let a: PlaylistResult = parse(&buf)?;
+
+        a.items
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .and_then(|x| x.snippet?.title)
+            .ok_or_else(|| Error {
+                code: 404,
+                message: "재생목록을 찾을 수 없습니다".to_owned(),
+            })?
+    };
+
+    let mut items = Vec::new();
+    let mut page_token = None;
+
+    loop {
+        let mut params = vec![
+            ("part", "snippet"),
+            ("maxResults", "50"),
+            ("key", youtube_api_key),
+            ("playlistId", playlist_id),
+        ];
+
+        if let Some(page_token) = page_token.as_deref() {
+            params.push(("pageToken", page_token));
+        }
+
+        let buf = reqwest::Client::new()
+            .get("https://www.googleapis.com/youtube/v3/playlistItems")
+            .query(&params)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+
+        let a: PlaylistItemsResult = parse(&buf)?;
+
+        items.extend(
+            a.items
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|item| AudioMetadata::try_from(item).ok()),
+        );
+
+        page_token = a.next_page_token;
+
+        if page_token.is_none() || items.len() >= limit {
+            break;
+        }
+    }
+
+    items.truncate(limit);
+
+    Ok(AudioPlaylist {
+        title,
+        url: format!("https://www.youtube.com/playlist?list={playlist_id}"),
+        items,
+    })
+}
+
 pub fn parse_vid(uri: Uri) -> String {
     #[derive(Deserialize)]
     struct Query {
@@ -329,3 +491,19 @@ pub fn parse_vid(uri: Uri) -> String {
         todo!("error handle");
     }
 }
+
+#[test]
+fn test_is_youtube_playlist_url() {
+    assert!(is_youtube_playlist_url(
+        "https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"
+    ));
+    assert!(is_youtube_playlist_url(
+        "https://www.youtube.com/watch?v=CLUDmYy9VP8&list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"
+    ));
+    assert!(!is_youtube_playlist_url(
+        "https://www.youtube.com/watch?v=CLUDmYy9VP8&list=RDCLUDmYy9VP8"
+    ));
+    assert!(!is_youtube_playlist_url(
+        "https://www.youtube.com/watch?v=CLUDmYy9VP8"
+    ));
+}
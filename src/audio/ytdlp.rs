@@ -0,0 +1,164 @@
+//! yt-dlp 실행 파일을 설치하고 최신 버전으로 업데이트함
+//!
+//! 설치된 버전은 `./yt-dlp.version`에 기록함
+
+use std::{cmp::Ordering, io, os::unix::fs::PermissionsExt, path::Path};
+
+use serde::Deserialize;
+use tokio::{fs, sync::Mutex};
+
+use super::YTDL;
+
+pub const YTDL_VERSION: &str = "./yt-dlp.version";
+
+pub const DEFAULT_RELEASE_URL: &str = "https://api.github.com/repos/yt-dlp/yt-dlp/releases/latest";
+
+const ASSET_NAME: &str = "yt-dlp";
+
+/// 업데이트하는 동안 다른 업데이트가 실행 파일을 덮어쓰지 않도록 함
+static UPDATE_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("reqwest: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    #[error("io: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid release: {0}")]
+    InvalidRelease(String),
+}
+
+/// GitHub 릴리스 API 응답 형식
+///
+/// 미러도 같은 형식으로 응답해야 함
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug)]
+pub enum Update {
+    Updated { from: Option<String>, to: String },
+    AlreadyLatest(String),
+}
+
+/// 설치된 버전. 설치되지 않았거나 기록이 없으면 None
+pub async fn installed_version() -> Option<String> {
+    if !Path::new(YTDL).exists() {
+        return None;
+    }
+
+    fs::read_to_string(YTDL_VERSION)
+        .await
+        .ok()
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+}
+
+/// 2025.10.22처럼 날짜로 된 태그를 숫자 단위로 비교함
+fn compare_version(a: &str, b: &str) -> Ordering {
+    let parse = |x: &str| {
+        x.split(['.', '-'])
+            .map(|x| x.parse::<u64>().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+
+    parse(a).cmp(&parse(b))
+}
+
+async fn get_latest_release(release_url: &str) -> Result<Release, Error> {
+    let resp = reqwest::Client::new()
+        .get(release_url)
+        .header(reqwest::header::USER_AGENT, "elgua")
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let buf = resp.bytes().await?;
+
+    serde_json::from_slice(&buf).map_err(|err| Error::InvalidRelease(err.to_string()))
+}
+
+/// 설치된 버전보다 새로운 버전이 있을 때만 받음
+pub async fn update(release_url: &str) -> Result<Update, Error> {
+    let _lock = UPDATE_LOCK.lock().await;
+
+    let installed_version = installed_version().await;
+    let release = get_latest_release(release_url).await?;
+
+    if let Some(installed_version) = &installed_version {
+        if compare_version(&release.tag_name, installed_version) != Ordering::Greater {
+            return Ok(Update::AlreadyLatest(installed_version.clone()));
+        }
+    }
+
+    let asset = release
+        .assets
+        .iter()
+        .find(|x| x.name == ASSET_NAME)
+        .ok_or_else(|| Error::InvalidRelease(format!("{ASSET_NAME} not found")))?;
+
+    let buf = reqwest::Client::new()
+        .get(&asset.browser_download_url)
+        .header(reqwest::header::USER_AGENT, "elgua")
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    // 받는 중에 실패해도 기존 실행 파일은 남아 있도록 다른 이름으로 받고 바꿈
+    let tmp = format!("{YTDL}.tmp");
+
+    fs::write(&tmp, &buf).await?;
+    fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o755)).await?;
+    fs::rename(&tmp, YTDL).await?;
+    fs::write(YTDL_VERSION, &release.tag_name).await?;
+
+    Ok(Update::Updated {
+        from: installed_version,
+        to: release.tag_name,
+    })
+}
+
+/// 시작할 때 호출함
+///
+/// 업데이트할 수 없어도 설치된 실행 파일이 있으면 그대로 사용함
+pub async fn init(release_url: &str) -> Result<(), Error> {
+    match update(release_url).await {
+        Ok(Update::Updated { from, to }) => {
+            log::info!("updated yt-dlp: {from:?} -> {to}");
+        }
+
+        Ok(Update::AlreadyLatest(version)) => {
+            log::info!("yt-dlp is already latest: {version}");
+        }
+
+        Err(err) if Path::new(YTDL).exists() => {
+            log::warn!("failed to update yt-dlp, using installed binary: {err}");
+        }
+
+        Err(err) => return Err(err),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compare_version() {
+    assert_eq!(
+        compare_version("2025.10.22", "2025.9.26"),
+        Ordering::Greater
+    );
+    assert_eq!(compare_version("2025.09.26", "2025.9.26"), Ordering::Equal);
+    assert_eq!(compare_version("2024.12.31", "2025.01.01"), Ordering::Less);
+}
@@ -1,6 +1,7 @@
-use std::{fs::File, io::Read};
+use std::{env, fs, path::Path};
 
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use serenity::{
     model::id::{ChannelId, GuildId},
     prelude::TypeMapKey,
@@ -9,37 +10,330 @@ use serenity::{
 #[derive(Clone, Deserialize)]
 pub struct Cfg {
     pub token: String,
-    pub guild_id: GuildId,
-    pub voice_channel_id: ChannelId,
-    pub history_channel_id: ChannelId,
+    /// 서버별 설정이 없을 때 guild_cfg에 옮겨 넣을 기존 단일 서버 설정
+    #[serde(default)]
+    pub guild_id: Option<GuildId>,
+    #[serde(default)]
+    pub voice_channel_id: Option<ChannelId>,
+    #[serde(default)]
+    pub history_channel_id: Option<ChannelId>,
     pub database_url: String,
     pub youtube_api_key: String,
     pub soundcloud_client_id: String,
+    /// ./cache/youtube, ./cache/soundcloud에 저장할 음악 파일의 최대 용량 (bytes)
+    #[serde(default = "default_cache_max_bytes")]
+    pub cache_max_bytes: u64,
+    /// yt-dlp 최신 릴리스를 가져올 주소. GitHub 릴리스 API와 같은 형식으로 응답하는 미러를 사용할 수 있음
+    #[serde(default = "default_ytdlp_release_url")]
+    pub ytdlp_release_url: String,
+    /// 재생 중인 음악이 없거나 음성 채널에 아무도 없을 때 음성 채널에서 나가기까지 기다리는 시간 (초)
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// 소리 크기를 정하지 않은 음악을 기본 소리 크기로 재생할 음량 (LUFS)
+    #[serde(default = "default_target_loudness")]
+    pub target_loudness: f64,
+    /// `/play`로 음악이 바뀔 때 두 음악을 겹쳐서 재생하고 `/stop`으로 중지할 때 소리를 줄이는 시간 (ms). 0(기본값)이면 바로 바꿈
+    #[serde(default)]
+    pub crossfade_ms: u64,
+}
+
+fn default_cache_max_bytes() -> u64 {
+    10 * 1024 * 1024 * 1024
+}
+
+fn default_ytdlp_release_url() -> String {
+    crate::audio::ytdlp::DEFAULT_RELEASE_URL.to_string()
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_target_loudness() -> f64 {
+    -14.0
 }
 
 impl Cfg {
-    pub fn new() -> Self {
-        #[cfg(debug_assertions)]
-        const CONFIG_FILE: &str = "./cfg.dev.json";
-        #[cfg(not(debug_assertions))]
-        const CONFIG_FILE: &str = "./cfg.json";
+    /// 설정 파일을 읽고 `ELGUA_*` 환경 변수로 덮어씀
+    ///
+    /// 설정 파일은 `--config <path>`, `ELGUA_CONFIG` 순서로 찾고
+    /// 둘 다 없으면 기본 설정 파일을 읽음. 기본 설정 파일은 없어도 됨
+    pub fn load() -> Result<Self, CfgError> {
+        let env = |key: &str| env::var(key).ok();
+
+        let file = match config_path(env::args().skip(1)).or_else(|| env(CONFIG_ENV)) {
+            Some(path) => read_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => read_file(DEFAULT_CONFIG_FILE)?,
+            None => Map::new(),
+        };
+
+        Self::from_layers(file, env)
+    }
+
+    /// 잘못된 값을 모두 모아서 한 번에 알려줌
+    fn from_layers(
+        mut file: Map<String, Value>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, CfgError> {
+        let mut errors = Vec::new();
+
+        for key in file.keys() {
+            if !FIELDS.iter().any(|x| x.name == key) {
+                log::warn!("unknown config field: {key}");
+            }
+        }
+
+        for field in FIELDS {
+            let env_key = format!("{ENV_PREFIX}{}", field.name.to_uppercase());
+            let env_file_key = format!("{env_key}_FILE");
 
-        let mut file = File::open(CONFIG_FILE).unwrap();
+            // k8s Secret처럼 파일로 마운트된 값은 *_FILE로 경로를 받음
+            let env_value = match (env(&env_key), env(&env_file_key)) {
+                (Some(_), Some(_)) => {
+                    errors.push(format!("{env_key}, {env_file_key}: set only one of them"));
+                    continue;
+                }
+                (Some(x), None) => Some(x),
+                (None, Some(path)) => match fs::read_to_string(&path) {
+                    Ok(x) => Some(x.trim_end().to_string()),
+                    Err(err) => {
+                        errors.push(format!("{env_file_key}: {path}: {err}"));
+                        continue;
+                    }
+                },
+                (None, None) => None,
+            };
 
-        let mut cfg_buf = Vec::new();
+            if let Some(env_value) = env_value {
+                match field.kind.parse(&env_value) {
+                    Ok(value) => {
+                        file.insert(field.name.to_string(), value);
+                    }
+                    Err(err) => {
+                        errors.push(format!("{env_key}: {err}"));
+                        continue;
+                    }
+                }
+            }
 
-        file.read_to_end(&mut cfg_buf).unwrap();
+            match file.get(field.name).filter(|x| !x.is_null()) {
+                Some(value) => {
+                    if let Err(err) = field.kind.check(value) {
+                        errors.push(format!("{}: {err}", field.name));
+                    }
+                }
+                None if field.required => {
+                    errors.push(format!("{}: missing (or set {env_key})", field.name));
+                }
+                None => {}
+            }
+        }
 
-        serde_json::from_slice(&cfg_buf).unwrap()
+        if !errors.is_empty() {
+            return Err(CfgError(errors));
+        }
+
+        serde_json::from_value(Value::Object(file)).map_err(|err| CfgError(vec![err.to_string()]))
     }
 }
 
-impl Default for Cfg {
-    fn default() -> Self {
-        Self::new()
+#[derive(Debug, thiserror::Error)]
+#[error("invalid config:\n{}", .0.join("\n"))]
+pub struct CfgError(Vec<String>);
+
+#[cfg(debug_assertions)]
+const DEFAULT_CONFIG_FILE: &str = "./cfg.dev.json";
+#[cfg(not(debug_assertions))]
+const DEFAULT_CONFIG_FILE: &str = "./cfg.json";
+
+/// 설정 파일 경로를 정하는 환경 변수
+const CONFIG_ENV: &str = "ELGUA_CONFIG";
+const CONFIG_FLAG: &str = "--config";
+
+/// 설정을 덮어쓰는 환경 변수의 접두사. `token` → `ELGUA_TOKEN`, `ELGUA_TOKEN_FILE`
+const ENV_PREFIX: &str = "ELGUA_";
+
+struct Field {
+    name: &'static str,
+    kind: FieldKind,
+    required: bool,
+}
+
+enum FieldKind {
+    Text,
+    Url,
+    Id,
+    Integer,
+    Number { min: f64, max: f64 },
+}
+
+const fn field(name: &'static str, kind: FieldKind, required: bool) -> Field {
+    Field {
+        name,
+        kind,
+        required,
+    }
+}
+
+/// `Cfg`의 필드와 같아야 함
+const FIELDS: &[Field] = &[
+    field("token", FieldKind::Text, true),
+    field("guild_id", FieldKind::Id, false),
+    field("voice_channel_id", FieldKind::Id, false),
+    field("history_channel_id", FieldKind::Id, false),
+    field("database_url", FieldKind::Text, true),
+    field("youtube_api_key", FieldKind::Text, true),
+    field("soundcloud_client_id", FieldKind::Text, true),
+    field("cache_max_bytes", FieldKind::Integer, false),
+    field("ytdlp_release_url", FieldKind::Url, false),
+    field("idle_timeout_secs", FieldKind::Integer, false),
+    field(
+        "target_loudness",
+        FieldKind::Number {
+            min: -70.0,
+            max: 0.0,
+        },
+        false,
+    ),
+    field("crossfade_ms", FieldKind::Integer, false),
+];
+
+impl FieldKind {
+    /// 환경 변수의 값을 설정 파일의 값처럼 바꿈
+    fn parse(&self, x: &str) -> Result<Value, String> {
+        match self {
+            Self::Text | Self::Url => Ok(Value::String(x.to_string())),
+            Self::Id | Self::Integer => x
+                .parse::<u64>()
+                .map(Value::from)
+                .map_err(|_| format!("expected an unsigned integer, got {x:?}")),
+            Self::Number { .. } => x
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| format!("expected a number, got {x:?}")),
+        }
+    }
+
+    fn check(&self, value: &Value) -> Result<(), String> {
+        match (self, value) {
+            (Self::Text, Value::String(x)) if x.trim().is_empty() => Err("empty".to_string()),
+            (Self::Text, Value::String(_)) => Ok(()),
+
+            (Self::Url, Value::String(x))
+                if x.starts_with("http://") || x.starts_with("https://") =>
+            {
+                Ok(())
+            }
+            (Self::Url, _) => Err(format!("expected an http(s) url, got {value}")),
+
+            // 디스코드 id는 문자열로 적어도 됨
+            (Self::Id, Value::String(x)) if x.parse::<u64>().is_ok_and(|x| x > 0) => Ok(()),
+            (Self::Id, Value::Number(x)) if x.as_u64().is_some_and(|x| x > 0) => Ok(()),
+            (Self::Id, _) => Err(format!("expected a discord id, got {value}")),
+
+            (Self::Integer, Value::Number(x)) if x.is_u64() => Ok(()),
+            (Self::Integer, _) => Err(format!("expected an unsigned integer, got {value}")),
+
+            (Self::Number { min, max }, Value::Number(x)) => match x.as_f64() {
+                Some(x) if (*min..=*max).contains(&x) => Ok(()),
+                _ => Err(format!(
+                    "expected a number between {min} and {max}, got {x}"
+                )),
+            },
+            (Self::Number { .. }, _) => Err(format!("expected a number, got {value}")),
+
+            (Self::Text, _) => Err(format!("expected a string, got {value}")),
+        }
+    }
+}
+
+/// `--config <path>`, `--config=<path>`
+fn config_path(mut args: impl Iterator<Item = String>) -> Option<String> {
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_FLAG {
+            return args.next();
+        }
+
+        if let Some(path) = arg
+            .strip_prefix(CONFIG_FLAG)
+            .and_then(|x| x.strip_prefix('='))
+        {
+            return Some(path.to_string());
+        }
+    }
+
+    None
+}
+
+fn read_file(path: &str) -> Result<Map<String, Value>, CfgError> {
+    let buf = fs::read(path).map_err(|err| CfgError(vec![format!("{path}: {err}")]))?;
+
+    match serde_json::from_slice(&buf) {
+        Ok(Value::Object(x)) => Ok(x),
+        Ok(_) => Err(CfgError(vec![format!("{path}: expected a json object")])),
+        Err(err) => Err(CfgError(vec![format!("{path}: {err}")])),
     }
 }
 
 impl TypeMapKey for Cfg {
     type Value = Cfg;
 }
+
+#[test]
+fn test_from_layers() {
+    let secret = env::temp_dir().join("elgua-test-token");
+    fs::write(&secret, "secret-token\n").unwrap();
+
+    let file = serde_json::json!({
+        "token": "file-token",
+        "database_url": "postgres://localhost/elgua",
+        "youtube_api_key": "yt",
+        "soundcloud_client_id": "sc",
+        "guild_id": "123",
+    });
+
+    let env = |key: &str| match key {
+        "ELGUA_TOKEN_FILE" => Some(secret.to_string_lossy().into_owned()),
+        "ELGUA_IDLE_TIMEOUT_SECS" => Some("60".to_string()),
+        _ => None,
+    };
+
+    let cfg = Cfg::from_layers(file.as_object().unwrap().clone(), env).unwrap();
+
+    assert_eq!(cfg.token, "secret-token");
+    assert_eq!(cfg.guild_id, Some(GuildId::new(123)));
+    assert_eq!(cfg.idle_timeout_secs, 60);
+    assert_eq!(cfg.crossfade_ms, 0);
+
+    fs::remove_file(&secret).ok();
+
+    let file = serde_json::json!({ "token": "", "target_loudness": 3.0 });
+
+    let env = |key: &str| match key {
+        "ELGUA_CACHE_MAX_BYTES" => Some("10GB".to_string()),
+        _ => None,
+    };
+
+    let CfgError(errors) = Cfg::from_layers(file.as_object().unwrap().clone(), env)
+        .err()
+        .unwrap();
+
+    let fields = errors
+        .iter()
+        .map(|x| x.split(':').next().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        fields,
+        [
+            "token",
+            "database_url",
+            "youtube_api_key",
+            "soundcloud_client_id",
+            "ELGUA_CACHE_MAX_BYTES",
+            "target_loudness"
+        ]
+    );
+}
@@ -1,5 +1,9 @@
+mod page_button;
 mod play_button;
 mod select_menu;
+mod vote_button;
 
+pub use page_button::*;
 pub use play_button::*;
 pub use select_menu::*;
+pub use vote_button::*;
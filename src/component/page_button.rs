@@ -0,0 +1,21 @@
+use serenity::{all::ButtonStyle, builder::CreateButton};
+
+/// 이전 페이지, 다음 페이지 버튼
+///
+/// custom_id: 이동할 페이지를 받아서 버튼의 custom_id를 만듦
+pub fn create_page_buttons(
+    page: usize,
+    last_page: usize,
+    custom_id: impl Fn(usize) -> String,
+) -> Vec<CreateButton> {
+    vec![
+        CreateButton::new(custom_id(page.saturating_sub(1)))
+            .label("이전")
+            .style(ButtonStyle::Secondary)
+            .disabled(page <= 1),
+        CreateButton::new(custom_id(page + 1))
+            .label("다음")
+            .style(ButtonStyle::Secondary)
+            .disabled(page >= last_page),
+    ]
+}
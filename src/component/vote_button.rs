@@ -0,0 +1,15 @@
+use serenity::{all::ButtonStyle, builder::CreateButton};
+
+/// 찬성, 반대 버튼
+///
+/// custom_id: 찬성하면 true를 받아서 버튼의 custom_id를 만듦
+pub fn create_vote_buttons(custom_id: impl Fn(bool) -> String) -> Vec<CreateButton> {
+    vec![
+        CreateButton::new(custom_id(true))
+            .label("찬성")
+            .style(ButtonStyle::Success),
+        CreateButton::new(custom_id(false))
+            .label("반대")
+            .style(ButtonStyle::Danger),
+    ]
+}
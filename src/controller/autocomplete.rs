@@ -0,0 +1,38 @@
+use serenity::{
+    all::{CreateAutocompleteResponse, Interaction},
+    builder::CreateInteractionResponse,
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+pub async fn autocomplete(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let Interaction::Autocomplete(command) = interaction else {
+        return Ok(());
+    };
+
+    let Some(option) = command.data.autocomplete() else {
+        return Ok(());
+    };
+
+    let guild_id = interaction.guild_id().unwrap();
+
+    let suggestions = match option.name {
+        "music" => {
+            usecase::autocomplete(ctx, guild_id, interaction.user().id, option.value).await?
+        }
+        _ => Vec::new(),
+    };
+
+    let response = suggestions
+        .into_iter()
+        .fold(CreateAutocompleteResponse::new(), |response, x| {
+            response.add_string_choice(x.name, x.url)
+        });
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await?;
+
+    Ok(())
+}
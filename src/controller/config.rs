@@ -0,0 +1,67 @@
+use serenity::{
+    all::{CommandDataOption, CommandDataOptionValue, Interaction},
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, store::CfgKey, usecase};
+
+pub enum Parameter {
+    Get(CfgKey),
+    Set(CfgKey, String),
+    Reset(CfgKey),
+    List,
+}
+
+fn get_string<'a>(options: &'a [CommandDataOption], name: &str) -> &'a str {
+    let x = options
+        .iter()
+        .find(|x| x.name == name)
+        .map(|x| &x.value)
+        .unwrap();
+
+    match x {
+        CommandDataOptionValue::String(x) => x,
+        _ => unreachable!(),
+    }
+}
+
+fn get_key(options: &[CommandDataOption]) -> CfgKey {
+    get_string(options, "key").parse().unwrap()
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let option = options.first().unwrap();
+
+        let CommandDataOptionValue::SubCommand(options) = &option.value else {
+            unreachable!()
+        };
+
+        match option.name.as_str() {
+            "get" => Self::Get(get_key(options)),
+            "set" => Self::Set(get_key(options), get_string(options, "value").to_string()),
+            "reset" => Self::Reset(get_key(options)),
+            "list" => Self::List,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub async fn config(
+    ctx: &Context,
+    interaction: &Interaction,
+    parameter: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let r = match parameter {
+        Parameter::Get(key) => usecase::config::get(ctx, guild_id, key).await?,
+        Parameter::Set(key, value) => usecase::config::set(ctx, guild_id, key, &value).await?,
+        Parameter::Reset(key) => usecase::config::reset(ctx, guild_id, key).await?,
+        Parameter::List => usecase::config::list(ctx, guild_id).await?,
+    };
+
+    interaction.send_ephemeral_message(&ctx.http, r).await?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use serenity::{
+    all::{CommandDataOption, CommandDataOptionValue, EditInteractionResponse, Interaction},
+    prelude::Context,
+};
+
+use crate::{audio::filter::Filter, interaction::InteractionExtension, usecase};
+
+pub struct Parameter(Filter);
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let x = &options.first().unwrap().value;
+
+        match x {
+            CommandDataOptionValue::String(x) => Self(x.parse().unwrap_or_default()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub async fn filter(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter(filter): Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    // 받고 있는 음악은 다 받은 다음에 필터를 적용함
+    interaction.defer(&ctx.http).await?;
+
+    let r = usecase::filter(ctx, guild_id, filter).await?;
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(r))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+use serenity::{all::Interaction, prelude::Context};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+use super::get_voice_channel_id;
+
+pub async fn join(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+    let voice_channel_id = get_voice_channel_id(ctx, guild_id, interaction.user().id).await?;
+
+    let r = usecase::join(ctx, guild_id, voice_channel_id).await?;
+
+    interaction.send_message(&ctx.http, r).await?;
+
+    Ok(())
+}
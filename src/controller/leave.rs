@@ -0,0 +1,13 @@
+use serenity::{all::Interaction, prelude::Context};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+pub async fn leave(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let r = usecase::leave(ctx, guild_id).await?;
+
+    interaction.send_message(&ctx.http, r).await?;
+
+    Ok(())
+}
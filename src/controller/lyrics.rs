@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use serenity::{
+    all::{EditInteractionResponse, Interaction},
+    prelude::Context,
+};
+
+use crate::{audio::AudioMetadata, interaction::InteractionExtension, usecase};
+
+/// 가사를 다시 보여주는 간격
+const UPDATE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// interaction token은 15분 동안만 응답을 수정할 수 있음
+const FOLLOW_MAX: Duration = Duration::from_secs(14 * 60);
+
+pub async fn lyrics(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    interaction.defer(&ctx.http).await?;
+
+    let (r, audio_metadata) = usecase::lyrics(ctx, guild_id).await?;
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(&r))
+        .await?;
+
+    let Some(audio_metadata) = audio_metadata else {
+        return Ok(());
+    };
+
+    // 명령어 처리가 끝나지 않으면 입력 중 표시가 계속 보여서 따로 실행함
+    tokio::spawn(follow(ctx.clone(), interaction.clone(), r, audio_metadata));
+
+    Ok(())
+}
+
+/// 음악이 끝나거나 바뀔 때까지 재생 시간에 맞춰서 바꿈
+async fn follow(
+    ctx: Context,
+    interaction: Interaction,
+    mut r: String,
+    audio_metadata: AudioMetadata,
+) {
+    let guild_id = interaction.guild_id().unwrap();
+    let started_at = Instant::now();
+
+    while started_at.elapsed() < FOLLOW_MAX {
+        tokio::time::sleep(UPDATE_INTERVAL).await;
+
+        let next = match usecase::lyrics::follow(&ctx, guild_id, &audio_metadata).await {
+            Ok(Some(next)) => next,
+            Ok(None) => break,
+            Err(err) => {
+                log::warn!("failed to follow lyrics: {err}");
+                break;
+            }
+        };
+
+        if next != r {
+            let edited = interaction
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(&next))
+                .await;
+
+            if let Err(err) = edited {
+                log::warn!("failed to update lyrics: {err}");
+                break;
+            }
+
+            r = next;
+        }
+    }
+}
@@ -1,9 +1,87 @@
+pub mod autocomplete;
+pub mod config;
+pub mod filter;
+pub mod join;
+pub mod leave;
+pub mod lyrics;
+pub mod pause;
 pub mod play;
+pub mod queue;
+pub mod resume;
+pub mod search;
+pub mod seek;
+pub mod setup;
+pub mod skip;
+pub mod stats;
 pub mod stop;
 pub mod track;
 pub mod volume;
+pub mod vote;
+pub mod ytdlp;
 
+pub use autocomplete::autocomplete;
+pub use config::config;
+pub use filter::filter;
+pub use join::join;
+pub use leave::leave;
+pub use lyrics::lyrics;
+pub use pause::pause;
 pub use play::play;
+pub use queue::queue;
+pub use resume::resume;
+pub use search::search;
+pub use seek::seek;
+pub use setup::setup;
+pub use skip::skip;
+pub use stats::stats;
 pub use stop::stop;
 pub use track::track;
 pub use volume::volume;
+pub use vote::vote;
+pub use ytdlp::ytdlp;
+
+use serenity::{
+    model::id::{ChannelId, GuildId, UserId},
+    prelude::Context,
+};
+
+use crate::store::Store;
+
+pub(crate) async fn get_history_channel_id(
+    ctx: &Context,
+    guild_id: GuildId,
+) -> crate::Result<Option<ChannelId>> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    Ok(store.guild_cfg().get(guild_id).await?.history_channel_id)
+}
+
+/// 명령어를 사용한 유저가 들어가 있는 음성 채널을 우선으로 사용하고
+/// 들어가 있지 않으면 서버에 설정된 음성 채널을 사용함
+pub(crate) async fn get_voice_channel_id(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> crate::Result<ChannelId> {
+    let user_voice_channel_id = ctx
+        .cache
+        .guild(guild_id)
+        .and_then(|guild| guild.voice_states.get(&user_id)?.channel_id);
+
+    if let Some(channel_id) = user_voice_channel_id {
+        return Ok(channel_id);
+    }
+
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    store
+        .guild_cfg()
+        .get(guild_id)
+        .await?
+        .voice_channel_id
+        .ok_or_else(|| {
+            crate::error::Error::CustomError("음성 채널에 먼저 들어가 주세요".to_owned())
+        })
+}
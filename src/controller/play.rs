@@ -1,6 +1,6 @@
 use serenity::{
     all::{
-        CommandDataOption, CommandDataOptionValue, ComponentInteractionData,
+        CommandData, CommandDataOption, CommandDataOptionValue, ComponentInteractionData,
         ComponentInteractionDataKind, EditInteractionResponse, Interaction,
     },
     prelude::Context,
@@ -8,20 +8,22 @@ use serenity::{
 };
 
 use crate::{
-    audio::{scdl, ytdl},
+    audio::{provider, ytdl, AudioSourceKind},
     cfg::Cfg,
     component::{create_numbering_select_menu, create_play_button},
     event::{Event, EventSender},
     interaction::InteractionExtension,
     route::Route,
-    usecase,
+    usecase::{self, vote::VoteAction},
 };
 
+use super::{get_voice_channel_id, queue::add_playlist, vote};
+
 #[derive(Debug)]
 pub struct Parameter {
-    keyword: String,
-    volume: Option<f32>,
-    play_count: Option<usize>,
+    pub(super) keyword: String,
+    pub(super) volume: Option<f32>,
+    pub(super) play_count: Option<usize>,
 }
 
 impl From<String> for Parameter {
@@ -36,15 +38,13 @@ impl From<String> for Parameter {
 
 impl From<&Vec<CommandDataOption>> for Parameter {
     fn from(options: &Vec<CommandDataOption>) -> Self {
+        // /play는 파일을 첨부하면 입력하지 않아도 됨
         let keyword = {
-            let x = options
-                .iter()
-                .find(|x| x.name == "music")
-                .map(|x| &x.value)
-                .unwrap();
+            let x = options.iter().find(|x| x.name == "music").map(|x| &x.value);
 
             match x {
-                CommandDataOptionValue::String(st) => st.clone(),
+                Some(CommandDataOptionValue::String(st)) => st.clone(),
+                None => String::new(),
                 _ => {
                     unreachable!()
                 }
@@ -89,6 +89,26 @@ impl From<&Vec<CommandDataOption>> for Parameter {
     }
 }
 
+/// 파일을 첨부하면 입력한 주소 대신 첨부된 파일의 주소를 재생함
+impl From<&CommandData> for Parameter {
+    fn from(data: &CommandData) -> Self {
+        let mut parameter = Self::from(&data.options);
+
+        let attachment = data
+            .options
+            .iter()
+            .find(|x| x.name == "attachment")
+            .and_then(|x| x.value.as_attachment_id())
+            .and_then(|id| data.resolved.attachments.get(&id));
+
+        if let Some(attachment) = attachment {
+            parameter.keyword = attachment.url.clone();
+        }
+
+        parameter
+    }
+}
+
 impl From<&ComponentInteractionData> for Parameter {
     fn from(data: &ComponentInteractionData) -> Self {
         match &data.kind {
@@ -129,30 +149,18 @@ impl From<&ComponentInteractionData> for Parameter {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum ContentKind {
-    YouTubeUrl,
-    SoundCloudUrl,
+pub(super) enum ContentKind {
+    Url(AudioSourceKind),
+    PlaylistUrl(AudioSourceKind),
     YouTubeSearchKeyword,
 }
 
 impl ContentKind {
     pub fn new(x: &str) -> Self {
-        if ytdl::is_youtube_url(x) {
-            Self::YouTubeUrl
-        } else if scdl::is_soundcloud_url(x) {
-            Self::SoundCloudUrl
-        } else {
-            Self::YouTubeSearchKeyword
-        }
-    }
-}
-
-impl From<ContentKind> for usecase::play::PlayableKind {
-    fn from(x: ContentKind) -> Self {
-        match x {
-            ContentKind::YouTubeUrl => usecase::play::PlayableKind::YouTube,
-            ContentKind::SoundCloudUrl => usecase::play::PlayableKind::SoundCloud,
-            _ => unreachable!(),
+        match provider::find(x) {
+            Some(provider) if provider.is_playlist(x) => Self::PlaylistUrl(provider.kind()),
+            Some(provider) => Self::Url(provider.kind()),
+            None => Self::YouTubeSearchKeyword,
         }
     }
 }
@@ -180,18 +188,45 @@ pub async fn play(
         play_count,
     } = parameter;
 
+    if keyword.is_empty() {
+        interaction
+            .send_ephemeral_message(
+                &ctx.http,
+                "음악의 주소나 유튜브 검색어를 입력하거나 파일을 첨부해 주세요",
+            )
+            .await?;
+
+        return Ok(());
+    }
+
     let content_kind = ContentKind::new(&keyword);
     let user_id = interaction.user().id;
+    let guild_id = interaction.guild_id().unwrap();
 
     log::info!("content_kind={content_kind:?}");
 
     match content_kind {
-        ContentKind::YouTubeUrl | ContentKind::SoundCloudUrl => {
-            let url = if keyword.contains("youtube.com/shorts/") {
-                keyword.replacen("shorts", "watch", 1)
-            } else {
-                keyword
-            };
+        ContentKind::PlaylistUrl(kind) => {
+            let parameter = usecase::play::Parameter::new(kind, keyword, volume, play_count);
+
+            add_playlist(ctx, interaction, parameter).await?;
+        }
+
+        ContentKind::Url(kind) => {
+            let url = kind.provider().normalize_url(keyword);
+
+            // 다른 사람이 재생한 음악을 바꾸려면 투표해야 할 수 있음
+            if vote::is_required(ctx, interaction).await? {
+                let voice_channel_id = get_voice_channel_id(ctx, guild_id, user_id).await?;
+                let parameter = usecase::play::Parameter::new(kind, url, volume, play_count);
+
+                let action = VoteAction::Replace {
+                    voice_channel_id,
+                    parameter,
+                };
+
+                return vote::start(ctx, interaction, action).await;
+            }
 
             if do_interact {
                 interaction
@@ -205,10 +240,10 @@ pub async fn play(
                     .await?;
             }
 
-            let parameter =
-                usecase::play::Parameter::new(content_kind.into(), url.clone(), volume, play_count);
-            let (audio_metadata, volume, prev_message_id) =
-                usecase::play(ctx, cfg.guild_id, cfg.voice_channel_id, parameter).await?;
+            let parameter = usecase::play::Parameter::new(kind, url.clone(), volume, play_count);
+            let voice_channel_id = get_voice_channel_id(ctx, guild_id, user_id).await?;
+            let (audio_metadata, volume, prev_message_id, track_uuid) =
+                usecase::play(ctx, guild_id, voice_channel_id, user_id, parameter).await?;
 
             if do_interact {
                 interaction
@@ -216,13 +251,14 @@ pub async fn play(
                         let play_info_str = MessageBuilder::new()
                             .push_named_link(&audio_metadata.title, &audio_metadata.url)
                             .push("\n소리 크기: ")
-                            .push((volume * 100.0).to_string())
+                            .push((volume.get() * 100.0).to_string())
                             .push("\n재생 횟수: ")
                             .push(play_count.unwrap_or(1).to_string())
                             .build();
 
-                        let play_button = create_play_button(Route::PlayFromClickedButton(
-                            audio_metadata.url.clone(),
+                        let play_button = create_play_button(Route::PlayHistory(
+                            audio_metadata.kind(),
+                            audio_metadata.id.clone(),
                         ));
 
                         EditInteractionResponse::new()
@@ -234,7 +270,15 @@ pub async fn play(
                 // interaction.delete_response(&ctx.http).await.ok();
             }
 
-            let event = Event::Play(audio_metadata.clone(), volume, user_id, prev_message_id);
+            let event = Event::Play(
+                audio_metadata.clone(),
+                volume,
+                play_count,
+                user_id,
+                guild_id,
+                prev_message_id,
+                track_uuid,
+            );
             if let Err(err) = event_tx.send((ctx.clone(), event)).await {
                 panic!("closed event channel: {}", err)
             }
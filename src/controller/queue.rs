@@ -0,0 +1,212 @@
+use serenity::{
+    all::{
+        CommandDataOption, CommandDataOptionValue, CreateEmbed, CreateEmbedFooter,
+        EditInteractionResponse, Interaction,
+    },
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+use super::{
+    get_voice_channel_id,
+    play::{self, ContentKind},
+};
+
+pub enum Parameter {
+    Add(play::Parameter),
+    List,
+    Remove(usize),
+    Move(usize, usize),
+    Clear,
+}
+
+fn get_index(options: &[CommandDataOption], name: &str) -> usize {
+    let x = options
+        .iter()
+        .find(|x| x.name == name)
+        .map(|x| &x.value)
+        .unwrap();
+
+    match x {
+        CommandDataOptionValue::Integer(x) => *x as usize,
+        _ => unreachable!(),
+    }
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let option = options.first().unwrap();
+
+        let CommandDataOptionValue::SubCommand(options) = &option.value else {
+            unreachable!()
+        };
+
+        match option.name.as_str() {
+            "add" => Self::Add(play::Parameter::from(options)),
+            "list" => Self::List,
+            "remove" => Self::Remove(get_index(options, "index")),
+            "move" => Self::Move(get_index(options, "from"), get_index(options, "to")),
+            "clear" => Self::Clear,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// 재생목록을 대기열에 추가하고 재생 중인 음악이 없으면 바로 재생함
+pub(super) async fn add_playlist(
+    ctx: &Context,
+    interaction: &Interaction,
+    parameter: usecase::play::Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+    let user_id = interaction.user().id;
+
+    interaction
+        .send_message(&ctx.http, "재생목록을 불러오는 중")
+        .await?;
+
+    let (playlist, added) = usecase::queue::add_playlist(ctx, guild_id, user_id, parameter).await?;
+
+    let mut description = MessageBuilder::new()
+        .push("대기열에 추가했어요 : ")
+        .push(added.to_string())
+        .push("곡")
+        .to_owned();
+
+    let skipped = playlist.items.len() - added;
+
+    if skipped > 0 {
+        description
+            .push("\n대기열이 가득 차서 ")
+            .push(skipped.to_string())
+            .push("곡은 추가하지 못했어요");
+    }
+
+    if added > 0 && !usecase::queue::is_playing(ctx, guild_id).await {
+        let voice_channel_id = get_voice_channel_id(ctx, guild_id, user_id).await?;
+
+        if let Some(audio_metadata) =
+            usecase::queue::play_next(ctx, guild_id, voice_channel_id).await?
+        {
+            description
+                .push("\n재생하는 중 : ")
+                .push_named_link(&audio_metadata.title, &audio_metadata.url);
+        }
+    }
+
+    let embed = CreateEmbed::new()
+        .title(&playlist.title)
+        .url(&playlist.url)
+        .description(description.build())
+        .footer(CreateEmbedFooter::new(format!(
+            "재생목록 {}곡",
+            playlist.items.len()
+        )));
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content("").embed(embed),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn queue(
+    ctx: &Context,
+    interaction: &Interaction,
+    parameter: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let r = match parameter {
+        Parameter::Add(play::Parameter {
+            keyword,
+            volume,
+            play_count,
+        }) => {
+            let content_kind = ContentKind::new(&keyword);
+
+            let kind = match content_kind {
+                ContentKind::YouTubeSearchKeyword => {
+                    interaction
+                        .send_ephemeral_message(
+                            &ctx.http,
+                            "대기열에는 음악의 주소만 추가할 수 있어요",
+                        )
+                        .await?;
+
+                    return Ok(());
+                }
+
+                ContentKind::PlaylistUrl(kind) => {
+                    let parameter =
+                        usecase::play::Parameter::new(kind, keyword, volume, play_count);
+
+                    return add_playlist(ctx, interaction, parameter).await;
+                }
+
+                ContentKind::Url(kind) => kind,
+            };
+
+            let url = kind.provider().normalize_url(keyword);
+
+            interaction
+                .send_message(
+                    &ctx.http,
+                    MessageBuilder::new()
+                        .push("대기열에 추가하는 중 : ")
+                        .push(&url)
+                        .build(),
+                )
+                .await?;
+
+            let parameter = usecase::play::Parameter::new(kind, url, volume, play_count);
+            let (track_queue, position) =
+                usecase::queue::add(ctx, guild_id, interaction.user().id, parameter).await?;
+
+            let mut r = MessageBuilder::new()
+                .push("대기열에 추가했어요 : ")
+                .push_named_link(&track_queue.title, &track_queue.url)
+                .push("\n대기열 순서: ")
+                .push(position.to_string())
+                .to_owned();
+
+            // 재생 중인 음악이 없으면 바로 재생함
+            if !usecase::queue::is_playing(ctx, guild_id).await {
+                let voice_channel_id =
+                    get_voice_channel_id(ctx, guild_id, interaction.user().id).await?;
+
+                if let Some(audio_metadata) =
+                    usecase::queue::play_next(ctx, guild_id, voice_channel_id).await?
+                {
+                    r = MessageBuilder::new()
+                        .push("재생하는 중 : ")
+                        .push_named_link(&audio_metadata.title, &audio_metadata.url)
+                        .to_owned();
+                }
+            }
+
+            interaction
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(r.build()))
+                .await?;
+
+            return Ok(());
+        }
+
+        Parameter::List => usecase::queue::list(ctx, guild_id).await?,
+
+        Parameter::Remove(index) => usecase::queue::remove(ctx, guild_id, index).await?,
+
+        Parameter::Move(from, to) => usecase::queue::move_to(ctx, guild_id, from, to).await?,
+
+        Parameter::Clear => usecase::queue::clear(ctx, guild_id).await?,
+    };
+
+    interaction.send_message(&ctx.http, r).await?;
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use serenity::{
+    all::{
+        CommandDataOption, CommandDataOptionValue, CreateActionRow, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseMessage, Interaction,
+    },
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+
+use crate::{
+    audio::AudioMetadata,
+    component::{create_page_buttons, create_play_button},
+    interaction::InteractionExtension,
+    route::Route,
+    usecase::{self, search::PER_PAGE},
+};
+
+pub struct Parameter {
+    keyword: String,
+    page: usize,
+}
+
+impl Parameter {
+    pub fn new(keyword: String, page: usize) -> Self {
+        Self { keyword, page }
+    }
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let keyword = {
+            let x = options
+                .iter()
+                .find(|x| x.name == "keyword")
+                .map(|x| &x.value)
+                .unwrap();
+
+            match x {
+                CommandDataOptionValue::String(st) => st.clone(),
+                _ => unreachable!(),
+            }
+        };
+
+        Self { keyword, page: 1 }
+    }
+}
+
+/// 명령어로 검색하면 새 메세지로 응답하고
+/// 페이지 버튼을 누르면 검색 결과 메세지를 수정함
+pub async fn search(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter { keyword, page }: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let (audio_metadatas, total) = usecase::search(ctx, guild_id, &keyword, page).await?;
+
+    let message = create_search_result(&keyword, page, &audio_metadatas, total);
+
+    let builder = match interaction {
+        Interaction::Component(_) => CreateInteractionResponse::UpdateMessage(message),
+        _ => CreateInteractionResponse::Message(message),
+    };
+
+    interaction.create_response(&ctx.http, builder).await?;
+
+    Ok(())
+}
+
+fn create_search_result(
+    keyword: &str,
+    page: usize,
+    audio_metadatas: &[AudioMetadata],
+    total: usize,
+) -> CreateInteractionResponseMessage {
+    if audio_metadatas.is_empty() {
+        return CreateInteractionResponseMessage::new()
+            .content("검색된 음악이 없어요")
+            .embeds(Vec::new())
+            .components(Vec::new());
+    }
+
+    let last_page = total.div_ceil(PER_PAGE);
+    let offset = (page - 1) * PER_PAGE;
+
+    let mut description = MessageBuilder::new();
+
+    for (i, x) in audio_metadatas.iter().enumerate() {
+        description
+            .push((offset + i + 1).to_string())
+            .push(". ")
+            .push_named_link(&x.title, &x.url)
+            .push(" - ")
+            .push_safe(&x.uploaded_by)
+            .push("\n");
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("검색 결과 : {keyword}"))
+        .description(description.build())
+        .footer(CreateEmbedFooter::new(format!(
+            "{page} / {last_page} 페이지 · {total}곡"
+        )));
+
+    let play_buttons = audio_metadatas
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            create_play_button(Route::PlayHistory(x.kind(), x.id.clone()))
+                .label(format!("{}. 재생하기", offset + i + 1))
+        })
+        .collect();
+
+    let page_buttons = create_page_buttons(page, last_page, |page| {
+        Route::SearchPage(page, keyword.to_string()).into()
+    });
+
+    CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(embed)
+        .components(vec![
+            CreateActionRow::Buttons(play_buttons),
+            CreateActionRow::Buttons(page_buttons),
+        ])
+}
@@ -0,0 +1,44 @@
+use serenity::{
+    all::{CommandDataOption, CommandDataOptionValue, Interaction},
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, usecase, util::time::parse_human_duration};
+
+pub struct Parameter(String);
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let x = &options.first().unwrap().value;
+
+        match x {
+            CommandDataOptionValue::String(x) => Self(x.clone()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub async fn seek(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter(time): Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let Some(position) = parse_human_duration(&time) else {
+        interaction
+            .send_ephemeral_message(
+                &ctx.http,
+                "시간을 1:23:45, 90s, 2m 같은 형식으로 입력해 주세요",
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    let r = usecase::seek(ctx, guild_id, position).await?;
+
+    interaction.send_message(&ctx.http, r).await?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use serenity::{
+    all::{CommandDataOption, CommandDataOptionValue, Interaction},
+    model::id::ChannelId,
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+pub struct Parameter {
+    voice_channel_id: Option<ChannelId>,
+    history_channel_id: Option<ChannelId>,
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let get_channel_id = |name: &str| {
+            let x = options.iter().find(|x| x.name == name).map(|x| &x.value);
+
+            match x {
+                Some(CommandDataOptionValue::Channel(channel_id)) => Some(*channel_id),
+                None => None,
+                _ => unreachable!(),
+            }
+        };
+
+        Self {
+            voice_channel_id: get_channel_id("voice_channel"),
+            history_channel_id: get_channel_id("history_channel"),
+        }
+    }
+}
+
+pub async fn setup(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter {
+        voice_channel_id,
+        history_channel_id,
+    }: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let r = usecase::setup(ctx, guild_id, voice_channel_id, history_channel_id).await?;
+
+    interaction.send_ephemeral_message(&ctx.http, r).await?;
+
+    Ok(())
+}
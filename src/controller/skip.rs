@@ -0,0 +1,23 @@
+use serenity::{
+    all::{EditInteractionResponse, Interaction},
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+use super::get_voice_channel_id;
+
+pub async fn skip(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+    let voice_channel_id = get_voice_channel_id(ctx, guild_id, interaction.user().id).await?;
+
+    interaction.defer(&ctx.http).await?;
+
+    let r = usecase::skip(ctx, guild_id, voice_channel_id).await?;
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(r))
+        .await?;
+
+    Ok(())
+}
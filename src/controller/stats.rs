@@ -0,0 +1,185 @@
+use serenity::{
+    all::{
+        CommandDataOption, CommandDataOptionValue, CreateActionRow, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseMessage, Interaction,
+    },
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+
+use crate::{
+    component::create_page_buttons,
+    interaction::InteractionExtension,
+    route::Route,
+    usecase::{
+        self,
+        stats::{Category, Period, Stats, PER_PAGE},
+    },
+};
+
+pub struct Parameter {
+    category: Category,
+    period: Period,
+    page: usize,
+}
+
+impl Parameter {
+    pub fn new(category: Category, period: Period, page: usize) -> Self {
+        Self {
+            category,
+            period,
+            page,
+        }
+    }
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let option = options.first().unwrap();
+
+        let CommandDataOptionValue::SubCommand(options) = &option.value else {
+            unreachable!()
+        };
+
+        let category = option.name.parse().unwrap();
+
+        let period = options
+            .iter()
+            .find(|x| x.name == "period")
+            .map(|x| match &x.value {
+                CommandDataOptionValue::String(st) => st.parse().unwrap(),
+                _ => unreachable!(),
+            })
+            .unwrap_or_default();
+
+        Self {
+            category,
+            period,
+            page: 1,
+        }
+    }
+}
+
+/// 명령어로 조회하면 새 메세지로 응답하고
+/// 페이지 버튼을 누르면 메세지를 수정함
+pub async fn stats(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter {
+        category,
+        period,
+        page,
+    }: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    let (stats, total) = usecase::stats(ctx, guild_id, category, period, page).await?;
+
+    let message = create_stats(category, period, page, &stats, total);
+
+    let builder = match interaction {
+        Interaction::Component(_) => CreateInteractionResponse::UpdateMessage(message),
+        _ => CreateInteractionResponse::Message(message),
+    };
+
+    interaction.create_response(&ctx.http, builder).await?;
+
+    Ok(())
+}
+
+fn title(category: Category) -> &'static str {
+    match category {
+        Category::Tracks => "많이 재생한 음악",
+        Category::Users => "많이 재생한 유저",
+        Category::Channels => "많이 재생한 채널",
+        Category::Kinds => "음악을 가져온 곳",
+    }
+}
+
+fn period_label(period: Period) -> &'static str {
+    match period {
+        Period::Day => "최근 하루",
+        Period::Week => "최근 일주일",
+        Period::Month => "최근 30일",
+        Period::All => "전체 기간",
+    }
+}
+
+fn create_stats(
+    category: Category,
+    period: Period,
+    page: usize,
+    stats: &Stats,
+    total: usize,
+) -> CreateInteractionResponseMessage {
+    if total == 0 {
+        return CreateInteractionResponseMessage::new()
+            .content(format!("{}에 재생한 음악이 없어요", period_label(period)))
+            .embeds(Vec::new())
+            .components(Vec::new());
+    }
+
+    let last_page = total.div_ceil(PER_PAGE);
+    let offset = (page - 1) * PER_PAGE;
+
+    let mut description = MessageBuilder::new();
+
+    match stats {
+        Stats::Tracks(rankings) => {
+            for (i, x) in rankings.iter().enumerate() {
+                description
+                    .push(format!("{}. ", offset + i + 1))
+                    .push_named_link(&x.value.title, &x.value.url)
+                    .push(format!(" - {}회\n", x.count));
+            }
+        }
+
+        Stats::Users(rankings) => {
+            for (i, x) in rankings.iter().enumerate() {
+                description
+                    .push(format!("{}. ", offset + i + 1))
+                    .mention(&x.value)
+                    .push(format!(" - {}회\n", x.count));
+            }
+        }
+
+        Stats::Channels(rankings) => {
+            for (i, x) in rankings.iter().enumerate() {
+                description
+                    .push(format!("{}. ", offset + i + 1))
+                    .push_safe(&x.value)
+                    .push(format!(" - {}회\n", x.count));
+            }
+        }
+
+        Stats::Kinds(rankings) => {
+            let sum = rankings.iter().map(|x| x.count).sum::<u64>().max(1);
+
+            for x in rankings {
+                let name = x.value.provider().name();
+
+                description.push(format!(
+                    "{name} - {}회 ({}%)\n",
+                    x.count,
+                    x.count * 100 / sum
+                ));
+            }
+        }
+    }
+
+    let embed = CreateEmbed::new()
+        .title(format!("{} · {}", title(category), period_label(period)))
+        .description(description.build())
+        .footer(CreateEmbedFooter::new(format!(
+            "{page} / {last_page} 페이지"
+        )));
+
+    let page_buttons = create_page_buttons(page, last_page, |page| {
+        Route::StatsPage(category, period, page).into()
+    });
+
+    CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(embed)
+        .components(vec![CreateActionRow::Buttons(page_buttons)])
+}
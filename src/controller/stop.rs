@@ -1,9 +1,17 @@
 use serenity::{all::Interaction, prelude::Context};
 
-use crate::{interaction::InteractionExtension, usecase};
+use crate::{interaction::InteractionExtension, usecase, usecase::vote::VoteAction};
+
+use super::vote;
 
 pub async fn stop(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
-    let r = usecase::stop(ctx).await?;
+    let guild_id = interaction.guild_id().unwrap();
+
+    if vote::is_required(ctx, interaction).await? {
+        return vote::start(ctx, interaction, VoteAction::Stop).await;
+    }
+
+    let r = usecase::stop(ctx, guild_id).await?;
 
     interaction.send_message(&ctx.http, r).await?;
 
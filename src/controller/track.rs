@@ -3,7 +3,9 @@ use serenity::{all::Interaction, prelude::Context};
 use crate::{interaction::InteractionExtension, usecase};
 
 pub async fn track(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
-    let r = usecase::track(ctx).await?;
+    let guild_id = interaction.guild_id().unwrap();
+
+    let r = usecase::track(ctx, guild_id).await?;
 
     interaction.send_message(&ctx.http, r).await?;
 
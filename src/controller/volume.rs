@@ -3,7 +3,9 @@ use serenity::{
     prelude::Context,
 };
 
-use crate::{cfg::Cfg, interaction::InteractionExtension, usecase};
+use crate::{interaction::InteractionExtension, usecase};
+
+use super::get_history_channel_id;
 
 // pub struct Parameter {
 //     volume: f32,
@@ -32,15 +34,10 @@ pub async fn volume(
     interaction: &Interaction,
     Parameter(volume): Parameter,
 ) -> crate::Result<()> {
-    let cfg = {
-        let x = ctx.data.read().await;
-        let cfg = x.get::<Cfg>().cloned().unwrap();
-        // let event_tx = x.get::<EventSender>().cloned().unwrap();
-
-        cfg
-    };
+    let guild_id = interaction.guild_id().unwrap();
+    let history_channel_id = get_history_channel_id(ctx, guild_id).await?;
 
-    let x = usecase::volume(ctx, cfg.history_channel_id, volume).await?;
+    let x = usecase::volume(ctx, guild_id, history_channel_id, volume).await?;
 
     interaction.send_message(&ctx.http, x).await?;
 
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use serenity::{
+    all::{
+        CreateActionRow, CreateInteractionResponseMessage, EditInteractionResponse, Interaction,
+    },
+    builder::CreateInteractionResponse,
+    prelude::Context,
+};
+
+use crate::{
+    component::create_vote_buttons,
+    interaction::InteractionExtension,
+    permission::{self, Requirement},
+    route::Route,
+    usecase::{
+        self,
+        vote::{Cast, Started, VoteAction},
+    },
+};
+
+pub struct Parameter {
+    vote_id: u64,
+    agree: bool,
+}
+
+impl Parameter {
+    pub fn new(vote_id: u64, agree: bool) -> Self {
+        Self { vote_id, agree }
+    }
+}
+
+/// 투표하는 서버에서 다른 사람이 재생한 음악을 DJ가 아닌 사람이 조작하면 투표해야 함
+pub(super) async fn is_required(ctx: &Context, interaction: &Interaction) -> crate::Result<bool> {
+    let guild_id = interaction.guild_id().unwrap();
+
+    if !usecase::vote::is_enabled(ctx, guild_id).await? {
+        return Ok(false);
+    }
+
+    let denied =
+        permission::check(ctx, interaction.member(), guild_id, Requirement::TrackOwner).await?;
+
+    Ok(denied.is_some())
+}
+
+/// 통과하는 데 다른 사람의 찬성이 필요 없으면 바로 실행함
+pub(super) async fn start(
+    ctx: &Context,
+    interaction: &Interaction,
+    action: VoteAction,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+    let user_id = interaction.user().id;
+
+    let (vote_id, content, timeout) =
+        match usecase::vote::start(ctx, guild_id, user_id, action).await? {
+            Started::Voting(vote_id, content, timeout) => (vote_id, content, timeout),
+
+            Started::Passed(vote) => {
+                // 버튼으로 재생하면 버튼이 있던 메세지는 지워져서 새 메세지로 응답함
+                interaction
+                    .create_response(
+                        &ctx.http,
+                        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+                    )
+                    .await?;
+
+                let r = usecase::vote::run(ctx, guild_id, *vote).await?;
+
+                interaction
+                    .edit_response(&ctx.http, EditInteractionResponse::new().content(r))
+                    .await?;
+
+                return Ok(());
+            }
+
+            Started::Running => {
+                interaction
+                    .send_ephemeral_message(&ctx.http, "이미 진행 중인 투표가 있어요")
+                    .await?;
+
+                return Ok(());
+            }
+
+            Started::NotListening => {
+                interaction
+                    .send_ephemeral_message(&ctx.http, "음성 채널에 있는 사람만 투표할 수 있어요")
+                    .await?;
+
+                return Ok(());
+            }
+        };
+
+    let buttons = create_vote_buttons(|agree| Route::Vote(vote_id, agree).into());
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .components(vec![CreateActionRow::Buttons(buttons)]),
+            ),
+        )
+        .await?;
+
+    tokio::spawn(expire(ctx.clone(), interaction.clone(), vote_id, timeout));
+
+    Ok(())
+}
+
+/// 시간이 지날 때까지 통과하지 못하면 투표를 끝냄
+async fn expire(ctx: Context, interaction: Interaction, vote_id: u64, timeout: Duration) {
+    tokio::time::sleep(timeout).await;
+
+    let guild_id = interaction.guild_id().unwrap();
+
+    let Some(content) = usecase::vote::expire(&ctx, guild_id, vote_id).await else {
+        return;
+    };
+
+    let r = interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(format!("{content}\n투표 시간이 끝났어요"))
+                .components(vec![]),
+        )
+        .await;
+
+    if let Err(err) = r {
+        log::warn!("failed to close vote: {err}");
+    }
+}
+
+pub async fn vote(
+    ctx: &Context,
+    interaction: &Interaction,
+    Parameter { vote_id, agree }: Parameter,
+) -> crate::Result<()> {
+    let guild_id = interaction.guild_id().unwrap();
+    let user_id = interaction.user().id;
+
+    let update = |content: String| {
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(vec![]),
+        )
+    };
+
+    match usecase::vote::cast(ctx, guild_id, vote_id, user_id, agree).await? {
+        Cast::Voting(content) => {
+            let buttons = create_vote_buttons(|agree| Route::Vote(vote_id, agree).into());
+
+            interaction
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content(content)
+                            .components(vec![CreateActionRow::Buttons(buttons)]),
+                    ),
+                )
+                .await?;
+        }
+
+        Cast::Passed(vote, content) => {
+            interaction
+                .create_response(&ctx.http, update(format!("{content}\n투표를 통과했어요")))
+                .await?;
+
+            // 실패해도 투표 메세지에 남김
+            let r = match usecase::vote::run(ctx, guild_id, *vote).await {
+                Ok(r) => r,
+                Err(err) => err.to_string(),
+            };
+
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(format!("{content}\n투표를 통과했어요\n{r}")),
+                )
+                .await?;
+        }
+
+        Cast::Rejected(content) => {
+            interaction
+                .create_response(&ctx.http, update(format!("{content}\n투표가 부결되었어요")))
+                .await?;
+        }
+
+        Cast::NotListening => {
+            interaction
+                .send_ephemeral_message(&ctx.http, "음성 채널에 있는 사람만 투표할 수 있어요")
+                .await?;
+        }
+
+        Cast::Closed => {
+            interaction
+                .send_ephemeral_message(&ctx.http, "이미 끝난 투표예요")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use serenity::{
+    all::{CommandDataOption, EditInteractionResponse, Interaction},
+    prelude::Context,
+};
+
+use crate::{interaction::InteractionExtension, usecase};
+
+pub enum Parameter {
+    Update,
+}
+
+impl From<&Vec<CommandDataOption>> for Parameter {
+    fn from(options: &Vec<CommandDataOption>) -> Self {
+        let option = options.first().unwrap();
+
+        match option.name.as_str() {
+            "update" => Self::Update,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub async fn ytdlp(
+    ctx: &Context,
+    interaction: &Interaction,
+    parameter: Parameter,
+) -> crate::Result<()> {
+    match parameter {
+        Parameter::Update => {
+            interaction
+                .send_ephemeral_message(&ctx.http, "yt-dlp의 최신 버전을 확인하는 중")
+                .await?;
+
+            let r = match usecase::ytdlp::update(ctx).await {
+                Ok(r) => r,
+                Err(err) => format!("yt-dlp를 업데이트하지 못했어요 : {err}"),
+            };
+
+            interaction
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(r))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::io;
 
 use songbird::{error::JoinError, input, tracks};
 
-use crate::audio::{self, scdl, ytdl};
+use crate::audio::{self, scdl, ytdl, ytdlp};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -36,34 +36,12 @@ pub enum Error {
     #[error("soundcloud_api: {0}")]
     SoundCloudApiError(#[from] scdl::Error),
 
+    #[error("yt-dlp: {0}")]
+    YtDlpError(#[from] ytdlp::Error),
+
+    #[error("tantivy: {0}")]
+    SearchError(#[from] tantivy::TantivyError),
+
     #[error("error: {0}")]
     CustomError(String),
-    // #[error("toshi::ToshiClientError {0}")]
-    // ToshiClientError(#[from] toshi::ToshiClientError),
-    // #[error("ToshiError {0}")]
-    // ToshiError(#[from] ToshiError),
 }
-
-// #[derive(Debug, Deserialize)]
-// pub struct ToshiError {
-//     #[serde(default)]
-//     pub status: u16,
-//     pub message: String,
-// }
-
-// impl std::error::Error for ToshiError {}
-
-// impl std::fmt::Display for ToshiError {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         write!(f, "status = {}; message = {}", self.status, self.message)
-//     }
-// }
-
-// impl Default for ToshiError {
-//     fn default() -> Self {
-//         Self {
-//             status: 0,
-//             message: "unknown error".to_string(),
-//         }
-//     }
-// }
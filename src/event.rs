@@ -1,20 +1,24 @@
 use std::ops::Deref;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serenity::{
     all::CreateMessage,
     builder::{CreateEmbed, CreateEmbedAuthor},
-    model::{id::UserId, prelude::MessageId},
+    model::{
+        id::{GuildId, UserId},
+        prelude::MessageId,
+    },
     prelude::{Context, TypeMapKey},
 };
 use tokio::sync::mpsc::{Receiver, Sender};
+use uuid::Uuid;
 
 use crate::{
-    audio::{AudioMetadata, AudioSourceKind},
-    cfg::Cfg,
+    audio::{filter::Filter, loudness::Volume, AudioMetadata},
     component::create_play_button,
     route::Route,
-    store::{History, Store},
+    store::{History, Play, Store},
+    track::ActiveFilter,
 };
 
 #[derive(Debug, Clone)]
@@ -40,8 +44,20 @@ impl Deref for EventSender {
 
 #[derive(Debug, Clone)]
 pub enum Event {
-    /// metadata, volume
-    Play(AudioMetadata, f32, UserId, Option<MessageId>),
+    /// metadata, volume, play count, user, guild, previous history message, track
+    Play(
+        AudioMetadata,
+        Volume,
+        Option<usize>,
+        UserId,
+        GuildId,
+        Option<MessageId>,
+        Uuid,
+    ),
+    /// track, ended at
+    End(Uuid, DateTime<Utc>),
+    /// track, restarted track
+    Restart(Uuid, Uuid),
 }
 
 pub async fn process(mut rx: Receiver<(Context, Event)>) {
@@ -53,29 +69,34 @@ pub async fn process(mut rx: Receiver<(Context, Event)>) {
 }
 
 async fn handle(ctx: Context, event: Event) -> crate::Result<()> {
-    let Cfg {
-        history_channel_id, ..
-    } = {
-        let x = ctx.data.read().await;
-        x.get::<Cfg>().cloned().unwrap()
-    };
-
     match event {
         // 재생하고나서 history channel에 매세지 보냄
-        Event::Play(audio_metadata, volume, user_id, prev_message_id) => {
+        Event::Play(
+            audio_metadata,
+            volume,
+            play_count,
+            user_id,
+            guild_id,
+            prev_message_id,
+            track_uuid,
+        ) => {
             let x = ctx.data.read().await;
 
+            let history_channel_id = {
+                let store = x.get::<Store>().unwrap();
+                store.guild_cfg().get(guild_id).await?.history_channel_id
+            };
+
             let kind = audio_metadata.kind();
             let url = audio_metadata.url;
             let uid = audio_metadata.id;
-            let color = match kind {
-                AudioSourceKind::YouTube => 0xFF0000,
-                AudioSourceKind::SoundCloud => 0xF26F23,
-            };
+            let color = kind.provider().color();
             let now = Utc::now();
 
             // 1. delete prev message
-            if let Some(prev_message_id) = prev_message_id {
+            if let (Some(history_channel_id), Some(prev_message_id)) =
+                (history_channel_id, prev_message_id)
+            {
                 let _result_of_deleted_message = ctx
                     .http
                     .delete_message(history_channel_id, prev_message_id, None)
@@ -83,7 +104,8 @@ async fn handle(ctx: Context, event: Event) -> crate::Result<()> {
             }
 
             // 2. send message
-            let message = {
+            // history channel이 설정되지 않은 서버는 db에만 기록함
+            let message = if let Some(history_channel_id) = history_channel_id {
                 let user = user_id.to_user(&ctx.http).await?;
 
                 let author = {
@@ -98,16 +120,30 @@ async fn handle(ctx: Context, event: Event) -> crate::Result<()> {
                     .author(author)
                     .title(audio_metadata.title.as_str())
                     .field("채널", &audio_metadata.uploaded_by, true)
-                    .field("소리 크기", (volume * 100.0).to_string(), true)
-                    .url(&url)
+                    .field("소리 크기", (volume.get() * 100.0).to_string(), true)
                     .timestamp(now)
                     .color(color);
 
+                // 첨부 파일은 다시 재생할 때 사용하는 주소라서 링크로 걸 수 없음
+                if url.starts_with("http") {
+                    embed = embed.url(&url);
+                }
+
+                let filter = x
+                    .get::<ActiveFilter>()
+                    .and_then(|x| x.get(&guild_id))
+                    .map(|x| x.filter)
+                    .unwrap_or_default();
+
+                if filter != Filter::Off {
+                    embed = embed.field("필터", filter.name(), true);
+                }
+
                 if let Some(thumbnail_url) = audio_metadata.thumbnail_url {
                     embed = embed.image(thumbnail_url);
                 }
 
-                let play_button = create_play_button(Route::PlayFromClickedButton(url));
+                let play_button = create_play_button(Route::PlayHistory(kind, uid.clone()));
 
                 history_channel_id
                     .send_message(
@@ -116,29 +152,82 @@ async fn handle(ctx: Context, event: Event) -> crate::Result<()> {
                     )
                     .await
                     .ok()
+            } else {
+                None
             };
 
             // 3. add or update db
             {
                 let store = x.get::<Store>().unwrap();
 
-                let _history_id = {
+                let history_id = {
                     let history = History {
                         id: 0,
-                        message_id: message.map(|x| x.id.get()),
+                        message_id: None,
                         title: audio_metadata.title.clone(),
                         channel: audio_metadata.uploaded_by,
-                        kind: kind.into(),
+                        kind,
                         uid,
+                        url: Some(url),
                         user_id: user_id.get(),
-                        volume: (volume * 100.0) as u8,
+                        volume: None,
                         created_at: now,
                     };
 
                     store.history().add_or_update(&history).await?
                 };
+
+                // 재생 기록 메세지와 소리 크기는 서버마다 따로 기록함
+                store
+                    .history()
+                    .update_guild_message(
+                        guild_id.get(),
+                        history_id,
+                        message.map(|x| (x.channel_id.get(), x.id.get())),
+                    )
+                    .await?;
+
+                if let Some(volume) = volume.fixed() {
+                    store
+                        .history()
+                        .update_guild_volume(guild_id.get(), history_id, (volume * 100.0) as u8)
+                        .await?;
+                }
+
+                let play = Play {
+                    id: 0,
+                    history_id,
+                    guild_id: Some(guild_id.get()),
+                    user_id: user_id.get(),
+                    volume: (volume.get() * 100.0) as u8,
+                    play_count,
+                    track_uuid: Some(track_uuid),
+                    started_at: now,
+                    ended_at: None,
+                };
+
+                store.history().add_play(&play).await?;
             }
         }
+
+        // 재생이 끝나거나 중지되면 재생 기록에 끝난 시간을 남김
+        Event::End(track_uuid, ended_at) => {
+            let x = ctx.data.read().await;
+            let store = x.get::<Store>().unwrap();
+
+            store.history().end_play(track_uuid, ended_at).await?;
+        }
+
+        // 다시 재생한 음악이 끝날 때 재생 기록에 끝난 시간을 남기도록 함
+        Event::Restart(track_uuid, new_track_uuid) => {
+            let x = ctx.data.read().await;
+            let store = x.get::<Store>().unwrap();
+
+            store
+                .history()
+                .replace_track_uuid(track_uuid, new_track_uuid)
+                .await?;
+        }
     }
 
     Ok(())
@@ -1,18 +1,21 @@
 use serenity::{
     all::{
-        CommandOptionType, CreateCommand, CreateCommandOption, CreateInteractionResponse,
-        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, Interaction,
-        InteractionType,
+        ChannelType, Command, CommandOptionType, CreateCommand, CreateCommandOption,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, Interaction, InteractionType,
     },
-    model::prelude::Ready,
+    model::{prelude::Ready, voice::VoiceState},
     prelude::{Context, EventHandler},
 };
 use tap::TapFallible;
 
 use crate::{
+    audio::filter::Filter,
     cfg::Cfg,
     interaction::InteractionExtension,
-    route::{route_application_command, route_message_component},
+    route::{route_application_command, route_autocomplete, route_message_component, Route},
+    store::{CfgKey, PLAY_COUNT_LIMIT},
+    usecase,
 };
 
 pub struct Handler;
@@ -36,6 +39,13 @@ impl EventHandler for Handler {
                 }
             }
 
+            // 응답하지 않으면 추천이 보이지 않을 뿐이라서 기록만 남김
+            InteractionType::Autocomplete => {
+                if let Err(err) = route_autocomplete(&ctx, &interaction).await {
+                    log::error!("{err:?}");
+                }
+            }
+
             InteractionType::Component => {
                 if let Err(err) = route_message_component(&ctx, &interaction).await {
                     log::error!("{err:?}");
@@ -57,12 +67,27 @@ impl EventHandler for Handler {
         }
     }
 
+    async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        usecase::idle::voice_state_update(&ctx, old.as_ref(), &new)
+            .await
+            .tap_err(|err| log::error!("{err:?}"))
+            .ok();
+    }
+
     async fn ready(&self, ctx: Context, _ready: Ready) {
         let x = ctx.data.read().await;
         let cfg = x.get::<Cfg>().unwrap();
 
-        cfg.guild_id
-            .set_commands(&ctx.http, commands())
+        // 예전에 단일 서버에 등록했던 명령어는 전역 명령어와 겹치지 않도록 지움
+        if let Some(guild_id) = cfg.guild_id {
+            guild_id
+                .set_commands(&ctx.http, Vec::new())
+                .await
+                .tap_err(|err| log::error!("{err:?}"))
+                .ok();
+        }
+
+        Command::set_global_commands(&ctx.http, commands())
             .await
             .unwrap();
     }
@@ -70,46 +95,172 @@ impl EventHandler for Handler {
 
 fn commands() -> Vec<CreateCommand> {
     vec![
-        CreateCommand::new("ping").description("퐁"),
-        CreateCommand::new("play")
+        command(Route::Ping).description("퐁"),
+        command(Route::Play)
             .description("음악을 재생해요")
             .set_options(vec![
+                music_option(),
+                attachment_option(),
+                volume_option(),
+                play_count_option(),
+            ]),
+        command(Route::Volume)
+            .description("재생 중인 음악의 소리 크기를 조절해요.")
+            .set_options(vec![volume_option().required(true)]),
+        command(Route::Stop).description("재생 중인 음악을 중지해요."),
+        command(Route::Track).description("재생 중인 음악의 정보를 가져와요."),
+        command(Route::Lyrics).description("재생 중인 음악의 가사를 재생 시간에 맞춰서 보여줘요."),
+        command(Route::Pause).description("재생 중인 음악을 일시정지해요."),
+        command(Route::Resume).description("일시정지한 음악을 다시 재생해요."),
+        command(Route::Seek)
+            .description("재생 중인 음악의 재생 위치를 옮겨요.")
+            .set_options(vec![CreateCommandOption::new(
+                CommandOptionType::String,
+                "time",
+                "옮길 위치를 입력해 주세요. (예: 1:23:45, 90s, 2m)",
+            )
+            .max_length(20)
+            .required(true)]),
+        command(Route::Filter)
+            .description("재생 중인 음악에 효과를 적용해요.")
+            .set_options(vec![filter_option()]),
+        command(Route::Skip)
+            .description("재생 중인 음악을 건너뛰고 대기열의 다음 음악을 재생해요."),
+        command(Route::Search)
+            .description("재생 기록에서 음악을 검색해요.")
+            .set_options(vec![CreateCommandOption::new(
+                CommandOptionType::String,
+                "keyword",
+                "제목, 채널 또는 @유저를 입력해 주세요.",
+            )
+            .max_length(80)
+            .required(true)]),
+        command(Route::Stats)
+            .description("이 서버에서 재생한 기록을 집계해요.")
+            .set_options(vec![
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "tracks",
+                    "많이 재생한 음악을 보여줘요.",
+                )
+                .add_sub_option(period_option()),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "users",
+                    "많이 재생한 유저를 보여줘요.",
+                )
+                .add_sub_option(period_option()),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "channels",
+                    "많이 재생한 음악의 채널을 보여줘요.",
+                )
+                .add_sub_option(period_option()),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "kinds",
+                    "유튜브와 사운드클라우드에서 재생한 비율을 보여줘요.",
+                )
+                .add_sub_option(period_option()),
+            ]),
+        command(Route::Join).description("들어가 있는 음성 채널로 불러요."),
+        command(Route::Leave).description("음성 채널에서 나가요."),
+        command(Route::Queue)
+            .description("대기열을 관리해요.")
+            .set_options(vec![
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "add",
+                    "대기열에 음악을 추가해요.",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "music",
+                        "음악의 주소를 입력해 주세요.",
+                    )
+                    .required(true),
+                )
+                .add_sub_option(volume_option())
+                .add_sub_option(play_count_option()),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "list",
+                    "대기열을 보여줘요.",
+                ),
                 CreateCommandOption::new(
-                    CommandOptionType::String,
-                    "music",
-                    "음악의 주소 또는 유튜브 검색어를 입력해 주세요.",
+                    CommandOptionType::SubCommand,
+                    "remove",
+                    "대기열에서 음악을 삭제해요.",
                 )
-                .required(true),
+                .add_sub_option(index_option("index", "삭제할 음악의 순서를 입력해 주세요.")),
                 CreateCommandOption::new(
-                    CommandOptionType::Integer,
-                    "volume",
-                    "음악의 소리 크기(1 ~ 100)를 입력해 주세요.",
+                    CommandOptionType::SubCommand,
+                    "move",
+                    "대기열에서 음악의 순서를 옮겨요.",
                 )
-                .min_int_value(1)
-                .max_int_value(100)
+                .add_sub_option(index_option("from", "옮길 음악의 순서를 입력해 주세요."))
+                .add_sub_option(index_option("to", "옮겨질 순서를 입력해 주세요.")),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "clear",
+                    "대기열을 비워요.",
+                ),
+            ]),
+        command(Route::Setup)
+            .description("이 서버에서 사용할 채널을 설정해요.")
+            .set_options(vec![
+                CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "voice_channel",
+                    "음성 채널에 들어가 있지 않을 때 사용할 음성 채널을 선택해 주세요.",
+                )
+                .channel_types(vec![ChannelType::Voice, ChannelType::Stage])
                 .required(false),
                 CreateCommandOption::new(
-                    CommandOptionType::Integer,
-                    "play_count",
-                    "재생 횟수를 입력해 주세요.",
+                    CommandOptionType::Channel,
+                    "history_channel",
+                    "재생 기록을 남길 채널을 선택해 주세요.",
                 )
-                .min_int_value(1)
-                .max_int_value(523)
+                .channel_types(vec![ChannelType::Text])
                 .required(false),
             ]),
-        CreateCommand::new("volume")
-            .description("재생 중인 음악의 소리 크기를 조절해요.")
+        command(Route::YtDlp)
+            .description("yt-dlp를 관리해요.")
             .set_options(vec![CreateCommandOption::new(
-                CommandOptionType::Integer,
-                "volume",
-                "음악의 소리 크기(1 ~ 100)를 입력해 주세요.",
-            )
-            .min_int_value(1)
-            .max_int_value(100)
-            .required(true)]),
-        CreateCommand::new("stop").description("재생 중인 음악을 중지해요."),
-        CreateCommand::new("track").description("재생 중인 음악의 정보를 가져와요."),
-        CreateCommand::new("sc")
+                CommandOptionType::SubCommand,
+                "update",
+                "yt-dlp를 최신 버전으로 업데이트해요.",
+            )]),
+        command(Route::Config)
+            .description("이 서버의 설정을 바꿔요.")
+            .set_options(vec![
+                CreateCommandOption::new(CommandOptionType::SubCommand, "get", "설정을 보여줘요.")
+                    .add_sub_option(cfg_key_option()),
+                CreateCommandOption::new(CommandOptionType::SubCommand, "set", "설정을 바꿔요.")
+                    .add_sub_option(cfg_key_option())
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "value",
+                            "바꿀 값을 입력해 주세요.",
+                        )
+                        .max_length(100)
+                        .required(true),
+                    ),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "reset",
+                    "설정을 기본값으로 되돌려요.",
+                )
+                .add_sub_option(cfg_key_option()),
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "list",
+                    "모든 설정을 보여줘요.",
+                ),
+            ]),
+        command(Route::UpdateScApiKey)
             .description("SoundCloud Client ID를 업데이트해요")
             .set_options(vec![CreateCommandOption::new(
                 CommandOptionType::String,
@@ -118,33 +269,96 @@ fn commands() -> Vec<CreateCommand> {
             )
             .required(true)]),
     ]
+    .into_iter()
+    .map(|x| x.dm_permission(false))
+    .collect()
 }
 
-// fn keyword_option(
-//     option: &mut CreateApplicationCommandOption,
-// ) -> &mut CreateApplicationCommandOption {
-//     option
-//         .name("music")
-//         .description("음악의 주소 또는 유튜브 검색어를 적어주세요")
-//         .kind(CommandOptionType::String)
-// }
-
-// fn volume_option(
-//     option: &mut CreateApplicationCommandOption,
-// ) -> &mut CreateApplicationCommandOption {
-//     option
-//         .name("volume")
-//         .description("음악의 소리 크기(1 ~ 100)를 설정해 주세요")
-//         .kind(CommandOptionType::Integer)
-//         .min_int_value(1)
-//         .max_int_value(100)
-// }
-
-// fn play_count(option: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
-//     option
-//         .name("play_count")
-//         .description("재생할 횟수를 설정해 주세요")
-//         .kind(CommandOptionType::Integer)
-//         .min_int_value(1)
-//         .max_int_value(523)
-// }
+/// 사용할 수 있는 사람이 정해진 명령어는 디스코드에서도 기본 권한이 있는 사람에게만 보여줌
+fn command(route: Route) -> CreateCommand {
+    let permissions = route.requirement(&[]).default_member_permissions();
+    let command = CreateCommand::new(route);
+
+    match permissions {
+        Some(permissions) => command.default_member_permissions(permissions),
+        None => command,
+    }
+}
+
+fn music_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "music",
+        "음악의 주소 또는 유튜브 검색어를 입력해 주세요.",
+    )
+    .set_autocomplete(true)
+}
+
+fn attachment_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Attachment,
+        "attachment",
+        "재생할 음악 파일을 첨부해 주세요.",
+    )
+}
+
+fn volume_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Integer,
+        "volume",
+        "음악의 소리 크기(1 ~ 100)를 입력해 주세요.",
+    )
+    .min_int_value(1)
+    .max_int_value(100)
+    .required(false)
+}
+
+fn play_count_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Integer,
+        "play_count",
+        "재생 횟수를 입력해 주세요.",
+    )
+    .min_int_value(1)
+    .max_int_value(PLAY_COUNT_LIMIT)
+    .required(false)
+}
+
+fn index_option(name: &str, description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::Integer, name, description)
+        .min_int_value(1)
+        .required(true)
+}
+
+fn cfg_key_option() -> CreateCommandOption {
+    CfgKey::guild_keys().fold(
+        CreateCommandOption::new(CommandOptionType::String, "key", "설정을 선택해 주세요.")
+            .required(true),
+        |option, key| option.add_string_choice(key.description(), key.as_str()),
+    )
+}
+
+fn filter_option() -> CreateCommandOption {
+    Filter::ALL.into_iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "preset",
+            "적용할 효과를 선택해 주세요.",
+        )
+        .required(true),
+        |option, filter| option.add_string_choice(filter.name(), filter.as_str()),
+    )
+}
+
+fn period_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "period",
+        "집계할 기간을 선택해 주세요. (기본: 전체 기간)",
+    )
+    .add_string_choice("최근 하루", "day")
+    .add_string_choice("최근 일주일", "week")
+    .add_string_choice("최근 30일", "month")
+    .add_string_choice("전체 기간", "all")
+    .required(false)
+}
@@ -1,11 +1,13 @@
 use serenity::{
     all::{
-        ChannelId, CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        ChannelId, CommandInteraction, CreateAutocompleteResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
         EditInteractionResponse,
     },
     builder::CreateInteractionResponse,
     http::Http,
     model::{
+        guild::Member,
         prelude::{Interaction, Message},
         user::User,
     },
@@ -27,6 +29,8 @@ pub trait InteractionExtension {
 
     fn user(&self) -> &User;
 
+    fn member(&self) -> Option<&Member>;
+
     async fn create_response(
         &self,
         http: &Http,
@@ -50,6 +54,16 @@ pub trait InteractionExtension {
     async fn defer(&self, http: &Http) -> serenity::Result<()>;
 }
 
+/// 자동 완성에는 메세지로 응답할 수 없어서 추천 없이 응답함
+async fn respond_empty_choices(command: &CommandInteraction, http: &Http) -> serenity::Result<()> {
+    command
+        .create_response(
+            http,
+            CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new()),
+        )
+        .await
+}
+
 impl InteractionExtension for Interaction {
     async fn send_message(&self, http: &Http, content: impl Into<String>) -> serenity::Result<()> {
         let builder = CreateInteractionResponse::Message(
@@ -57,7 +71,7 @@ impl InteractionExtension for Interaction {
         );
         match self {
             Interaction::Ping(_ping) => Ok(()),
-            Interaction::Autocomplete(_command) => unreachable!(),
+            Interaction::Autocomplete(command) => respond_empty_choices(command, http).await,
             Interaction::Command(command) => command.create_response(http, builder).await,
             Interaction::Component(component) => component.create_response(http, builder).await,
             Interaction::Modal(modal) => modal.create_response(http, builder).await,
@@ -77,7 +91,7 @@ impl InteractionExtension for Interaction {
         );
         match self {
             Interaction::Ping(_ping) => Ok(()),
-            Interaction::Autocomplete(_command) => unreachable!(),
+            Interaction::Autocomplete(command) => respond_empty_choices(command, http).await,
             Interaction::Command(command) => command.create_response(http, builder).await,
             Interaction::Component(component) => component.create_response(http, builder).await,
             Interaction::Modal(modal) => modal.create_response(http, builder).await,
@@ -115,6 +129,16 @@ impl InteractionExtension for Interaction {
         }
     }
 
+    fn member(&self) -> Option<&Member> {
+        match self {
+            Interaction::Command(command) => command.member.as_deref(),
+            Interaction::Autocomplete(command) => command.member.as_deref(),
+            Interaction::Component(component) => component.member.as_ref(),
+            Interaction::Modal(modal) => modal.member.as_ref(),
+            _ => None,
+        }
+    }
+
     async fn defer(&self, http: &Http) -> serenity::Result<()> {
         match self {
             Interaction::Ping(_ping) => unreachable!(),
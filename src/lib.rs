@@ -6,6 +6,7 @@ pub mod error;
 pub mod event;
 pub mod handler;
 pub mod interaction;
+pub mod permission;
 pub mod route;
 pub mod store;
 pub mod track;
@@ -1,4 +1,12 @@
-use elgua::{cfg::Cfg, event, event::EventSender, handler::Handler, store::Store};
+use elgua::{
+    audio::{cache::AudioCache, provider, ytdlp},
+    cfg::Cfg,
+    event,
+    event::EventSender,
+    handler::Handler,
+    store::{GuildCfg, Store},
+    track::Track,
+};
 use log::LevelFilter;
 use serenity::{prelude::*, Client};
 use simple_logger::SimpleLogger;
@@ -21,17 +29,51 @@ async fn main() {
         .init()
         .unwrap();
 
-    // TODO: 업데이트 구현
-    // 이미 있는 것보다 최신 버전만 받아오도록
-    youtube_dl::download_yt_dlp(".").await.unwrap();
-    fs::create_dir_all(elgua::audio::YTDL_CACHE).await.unwrap();
-    fs::create_dir_all(elgua::audio::SCDL_CACHE).await.unwrap();
+    let cfg = match Cfg::load() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            log::error!("{err}");
+            std::process::exit(1);
+        }
+    };
 
-    let cfg = Cfg::new();
+    ytdlp::init(&cfg.ytdlp_release_url).await.unwrap();
+    for provider in provider::all() {
+        fs::create_dir_all(provider.kind().cache_dir())
+            .await
+            .unwrap();
+    }
     let store = Store::connect(&cfg).await;
+
+    AudioCache::sync(&store).await.unwrap();
+    AudioCache::evict(&store, cfg.cache_max_bytes, &Default::default())
+        .await
+        .unwrap();
+
+    if let Some(guild_id) = cfg.guild_id {
+        let guild_cfg = GuildCfg {
+            guild_id,
+            voice_channel_id: cfg.voice_channel_id,
+            history_channel_id: cfg.history_channel_id,
+        };
+
+        store.guild_cfg().add_if_absent(&guild_cfg).await.unwrap();
+        let claimed = store
+            .stats()
+            .claim_unknown_guild(guild_id.get())
+            .await
+            .unwrap();
+
+        // 검색 색인에 재생한 서버가 들어가 있음
+        if claimed > 0 {
+            store.history().reindex().await.unwrap();
+        }
+    }
+
     let (event_tx, event_rx) = mpsc::channel(12);
 
-    let intents = GatewayIntents::GUILD_VOICE_STATES;
+    // 명령어를 사용한 유저의 음성 채널을 찾기 위해 GUILDS로 서버 정보를 캐시함
+    let intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_VOICE_STATES;
 
     let mut client = Client::builder(&cfg.token, intents)
         .event_handler(Handler)
@@ -43,6 +85,7 @@ async fn main() {
         let mut x = client.data.write().await;
         x.insert::<Cfg>(cfg);
         x.insert::<Store>(store);
+        x.insert::<Track>(Default::default());
         x.insert::<EventSender>(EventSender::new(event_tx))
     }
 
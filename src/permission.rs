@@ -0,0 +1,178 @@
+//! 명령어마다 사용할 수 있는 사람을 정함
+//!
+//! 관리자 역할과 DJ 역할은 `/config`로 정함.
+//! 서버 관리 권한이 있으면 관리자 역할이 없어도 관리자로 봄.
+//! 모든 서버에 적용되는 설정은 봇 주인만 바꿀 수 있음
+
+use serenity::{
+    all::{CommandDataOption, Member, Permissions},
+    model::id::{GuildId, RoleId, UserId},
+    prelude::{Context, TypeMap},
+};
+use songbird::tracks::PlayMode;
+
+use crate::{
+    route::Route,
+    store::{CfgKey, Store},
+    track::{Requester, Track},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    Anyone,
+    /// 재생 중인 음악을 재생한 사람이나 DJ
+    TrackOwner,
+    Dj,
+    Admin,
+    /// 봇 주인. 모든 서버에 적용되는 명령어
+    Owner,
+}
+
+impl Requirement {
+    /// 명령어를 등록할 때 사용하는 기본 권한
+    ///
+    /// 관리자 역할이 있는 사람도 볼 수 있도록 관리자 명령어는 숨기지 않고 [`check`]에서 확인함
+    pub fn default_member_permissions(&self) -> Option<Permissions> {
+        match self {
+            Self::Owner => Some(Permissions::MANAGE_GUILD),
+            _ => None,
+        }
+    }
+
+    fn denied_message(&self) -> &'static str {
+        match self {
+            Self::Anyone => unreachable!(),
+            Self::TrackOwner => "음악을 재생한 사람이나 DJ만 사용할 수 있어요",
+            Self::Dj => "DJ만 사용할 수 있어요",
+            Self::Admin => "관리자만 사용할 수 있어요",
+            Self::Owner => "봇 주인만 사용할 수 있어요",
+        }
+    }
+}
+
+impl Route {
+    pub fn requirement(&self, options: &[CommandDataOption]) -> Requirement {
+        use Route::*;
+
+        match self {
+            Volume | Stop | Skip | Pause | Resume | Seek | Filter => Requirement::TrackOwner,
+
+            // 다른 사람이 추가한 음악도 바뀌기 때문에 DJ만 사용할 수 있음
+            Queue => match options.first().map(|x| x.name.as_str()) {
+                Some("remove" | "move" | "clear") => Requirement::Dj,
+                _ => Requirement::Anyone,
+            },
+
+            Leave => Requirement::Dj,
+
+            Setup | Config => Requirement::Admin,
+
+            // 모든 서버에 적용됨
+            YtDlp | UpdateScApiKey => Requirement::Owner,
+
+            _ => Requirement::Anyone,
+        }
+    }
+}
+
+/// 사용할 수 없으면 사용자에게 보여줄 메세지
+pub async fn check(
+    ctx: &Context,
+    member: Option<&Member>,
+    guild_id: GuildId,
+    requirement: Requirement,
+) -> crate::Result<Option<&'static str>> {
+    if requirement == Requirement::Anyone {
+        return Ok(None);
+    }
+
+    let Some(member) = member else {
+        return Ok(Some(requirement.denied_message()));
+    };
+
+    if requirement == Requirement::Owner {
+        let is_owner = is_owner(ctx, member.user.id).await?;
+
+        return Ok((!is_owner).then(|| requirement.denied_message()));
+    }
+
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let admin_role = get_role(store, guild_id, CfgKey::AdminRole).await?;
+    let dj_role = get_role(store, guild_id, CfgKey::DjRole).await?;
+
+    let has_role = |role: Option<RoleId>| role.is_some_and(|x| member.roles.contains(&x));
+
+    let is_admin = member
+        .permissions
+        .is_some_and(|x| x.administrator() || x.manage_guild())
+        || has_role(admin_role);
+
+    let is_allowed = match requirement {
+        Requirement::Anyone | Requirement::Owner => unreachable!(),
+        Requirement::Admin => is_admin,
+        Requirement::Dj => is_admin || has_role(dj_role),
+        Requirement::TrackOwner => {
+            is_admin || has_role(dj_role) || is_track_owner(&x, guild_id, member.user.id).await
+        }
+    };
+
+    Ok((!is_allowed).then(|| requirement.denied_message()))
+}
+
+async fn get_role(store: &Store, guild_id: GuildId, key: CfgKey) -> crate::Result<Option<RoleId>> {
+    let role = store
+        .elgua_cfg()
+        .get_or_default::<u64>(key, Some(guild_id))
+        .await?
+        .map(RoleId::new);
+
+    Ok(role)
+}
+
+/// 팀이 소유한 봇이면 팀 주인
+async fn is_owner(ctx: &Context, user_id: UserId) -> crate::Result<bool> {
+    let info = ctx.http.get_current_application_info().await?;
+
+    let owner_id = match (&info.team, &info.owner) {
+        (Some(team), _) => Some(team.owner_user_id),
+        (None, Some(owner)) => Some(owner.id),
+        (None, None) => None,
+    };
+
+    Ok(owner_id == Some(user_id))
+}
+
+/// 재생 중인 음악이 없으면 조작할 음악이 없어서 누구나 사용할 수 있음
+async fn is_track_owner(x: &TypeMap, guild_id: GuildId, user_id: UserId) -> bool {
+    let Some(Track(_, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) else {
+        return true;
+    };
+
+    let is_playing = track
+        .get_info()
+        .await
+        .is_ok_and(|x| matches!(x.playing, PlayMode::Play | PlayMode::Pause));
+
+    if !is_playing {
+        return true;
+    }
+
+    x.get::<Requester>()
+        .and_then(|x| x.get(&guild_id))
+        .is_none_or(|requester| *requester == user_id)
+}
+
+#[test]
+fn test_requirement() {
+    assert_eq!(Route::Stop.requirement(&[]), Requirement::TrackOwner);
+    assert_eq!(Route::Queue.requirement(&[]), Requirement::Anyone);
+    assert_eq!(Route::Config.requirement(&[]), Requirement::Admin);
+    assert_eq!(
+        Route::Config.requirement(&[]).default_member_permissions(),
+        None
+    );
+    assert_eq!(Route::UpdateScApiKey.requirement(&[]), Requirement::Owner);
+    assert_eq!(Route::YtDlp.requirement(&[]), Requirement::Owner);
+}
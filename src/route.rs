@@ -3,10 +3,14 @@ use std::str::FromStr;
 use serenity::all::{CommandDataOptionValue, Interaction};
 use serenity::prelude::Context;
 
-use crate::audio::scdl;
-use crate::cfg::Cfg;
+use crate::audio::{provider, scdl, AudioSourceKind};
 use crate::interaction::InteractionExtension;
+use crate::permission;
 use crate::store::{CfgKey, Store};
+use crate::usecase::{
+    self,
+    stats::{Category, Period},
+};
 
 use super::controller;
 
@@ -17,8 +21,25 @@ mod route_constant {
     pub const STOP: &str = "stop";
     pub const TRACK: &str = "track";
     pub const SEARCH: &str = "search";
+    pub const QUEUE: &str = "queue";
+    pub const SKIP: &str = "skip";
+    pub const SETUP: &str = "setup";
+    pub const JOIN: &str = "join";
+    pub const LEAVE: &str = "leave";
+    pub const YTDLP: &str = "ytdlp";
+    pub const PAUSE: &str = "pause";
+    pub const RESUME: &str = "resume";
+    pub const SEEK: &str = "seek";
+    pub const STATS: &str = "stats";
+    pub const FILTER: &str = "filter";
+    pub const LYRICS: &str = "lyrics";
+    pub const CONFIG: &str = "config";
     pub const PLAY_FROM_SELECTED_MENU: &str = "play-from-selected-menu";
     pub const PLAY_FROM_CLICKED_BUTTON: &str = "play-from-clicked-button#";
+    pub const PLAY_HISTORY: &str = "play-history#";
+    pub const SEARCH_PAGE: &str = "search-page#";
+    pub const VOTE: &str = "vote#";
+    pub const STATS_PAGE: &str = "stats-page#";
 
     pub const UPDATE_SC_API_KEY: &str = "sc";
 
@@ -33,8 +54,31 @@ pub enum Route {
     Stop,
     Track,
     Search,
+    Queue,
+    Skip,
+    Setup,
+    Join,
+    Leave,
+    YtDlp,
+    Pause,
+    Resume,
+    Seek,
+    Stats,
+    Filter,
+    Lyrics,
+    Config,
     PlayFromSelectedMenu,
     PlayFromClickedButton(String),
+    /// (kind, uid)
+    ///
+    /// custom_id는 100자까지라서 주소 대신 넣고 누르면 재생 기록에서 주소를 찾음
+    PlayHistory(AudioSourceKind, String),
+    /// (page, keyword)
+    SearchPage(usize, String),
+    /// (category, period, page)
+    StatsPage(Category, Period, usize),
+    /// (vote_id, agree)
+    Vote(u64, bool),
 
     UpdateScApiKey,
 }
@@ -56,12 +100,63 @@ impl From<Route> for String {
 
             Search => route_constant::SEARCH,
 
+            Queue => route_constant::QUEUE,
+
+            Skip => route_constant::SKIP,
+
+            Setup => route_constant::SETUP,
+
+            Join => route_constant::JOIN,
+
+            Leave => route_constant::LEAVE,
+
+            YtDlp => route_constant::YTDLP,
+
+            Pause => route_constant::PAUSE,
+
+            Resume => route_constant::RESUME,
+
+            Seek => route_constant::SEEK,
+
+            Stats => route_constant::STATS,
+
+            Filter => route_constant::FILTER,
+
+            Lyrics => route_constant::LYRICS,
+
+            Config => route_constant::CONFIG,
+
             PlayFromSelectedMenu => route_constant::PLAY_FROM_SELECTED_MENU,
 
             PlayFromClickedButton(url) => {
                 return format!("{}{url}", route_constant::PLAY_FROM_CLICKED_BUTTON)
             }
 
+            PlayHistory(kind, uid) => {
+                return format!("{}{}#{uid}", route_constant::PLAY_HISTORY, kind.as_str())
+            }
+
+            SearchPage(page, keyword) => {
+                return format!("{}{page}#{keyword}", route_constant::SEARCH_PAGE)
+            }
+
+            StatsPage(category, period, page) => {
+                return format!(
+                    "{}{}#{}#{page}",
+                    route_constant::STATS_PAGE,
+                    category.as_str(),
+                    period.as_str()
+                )
+            }
+
+            Vote(vote_id, agree) => {
+                return format!(
+                    "{}{vote_id}#{}",
+                    route_constant::VOTE,
+                    if agree { "yes" } else { "no" }
+                )
+            }
+
             UpdateScApiKey => route_constant::UPDATE_SC_API_KEY,
         }
         .to_owned()
@@ -76,6 +171,11 @@ impl FromStr for Route {
     }
 }
 
+/// 페이지는 1부터 시작함
+fn parse_page(x: &str) -> Result<usize, ()> {
+    x.parse().ok().filter(|x| *x >= 1).ok_or(())
+}
+
 impl TryFrom<&str> for Route {
     type Error = ();
 
@@ -95,6 +195,32 @@ impl TryFrom<&str> for Route {
 
             route_constant::SEARCH => Search,
 
+            route_constant::QUEUE => Queue,
+
+            route_constant::SKIP => Skip,
+
+            route_constant::SETUP => Setup,
+
+            route_constant::JOIN => Join,
+
+            route_constant::LEAVE => Leave,
+
+            route_constant::YTDLP => YtDlp,
+
+            route_constant::PAUSE => Pause,
+
+            route_constant::RESUME => Resume,
+
+            route_constant::SEEK => Seek,
+
+            route_constant::STATS => Stats,
+
+            route_constant::FILTER => Filter,
+
+            route_constant::LYRICS => Lyrics,
+
+            route_constant::CONFIG => Config,
+
             route_constant::PLAY_FROM_SELECTED_MENU => PlayFromSelectedMenu,
 
             route_constant::UPDATE_SC_API_KEY => UpdateScApiKey,
@@ -106,6 +232,45 @@ impl TryFrom<&str> for Route {
                 PlayFromClickedButton(url.to_string())
             }
 
+            x if x.starts_with(route_constant::PLAY_HISTORY) => {
+                let x = x.strip_prefix(route_constant::PLAY_HISTORY).unwrap();
+                let (kind, uid) = x.split_once('#').ok_or(())?;
+                let kind = provider::get(kind).ok_or(())?.kind();
+
+                PlayHistory(kind, uid.to_string())
+            }
+
+            x if x.starts_with(route_constant::SEARCH_PAGE) => {
+                let x = x.strip_prefix(route_constant::SEARCH_PAGE).unwrap();
+                let (page, keyword) = x.split_once('#').ok_or(())?;
+
+                SearchPage(parse_page(page)?, keyword.to_string())
+            }
+
+            x if x.starts_with(route_constant::STATS_PAGE) => {
+                let x = x.strip_prefix(route_constant::STATS_PAGE).unwrap();
+                let mut x = x.split('#');
+
+                let category = x.next().ok_or(())?.parse()?;
+                let period = x.next().ok_or(())?.parse()?;
+                let page = parse_page(x.next().ok_or(())?)?;
+
+                StatsPage(category, period, page)
+            }
+
+            x if x.starts_with(route_constant::VOTE) => {
+                let x = x.strip_prefix(route_constant::VOTE).unwrap();
+                let (vote_id, agree) = x.split_once('#').ok_or(())?;
+
+                let agree = match agree {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(()),
+                };
+
+                Vote(vote_id.parse().map_err(|_| ())?, agree)
+            }
+
             x if x.starts_with(route_constant::DEPRECATED_PLAY_FROM_CLICKED_BUTTON) => {
                 let (_, url) = x.split_once(';').unwrap();
 
@@ -127,25 +292,48 @@ pub async fn route_application_command(
         return Ok(());
     };
 
-    let history_channel_id = ctx
-        .data
-        .read()
-        .await
-        .get::<Cfg>()
-        .unwrap()
-        .history_channel_id;
-    let do_interact = command.channel_id != history_channel_id;
+    let Some(guild_id) = command.guild_id else {
+        interaction
+            .send_ephemeral_message(&ctx.http, "서버에서만 사용할 수 있어요")
+            .await?;
 
-    let typing = interaction.channel_id().start_typing(&ctx.http);
+        return Ok(());
+    };
+
+    let route = Route::try_from(command.data.name.as_str()).ok();
     let options = &command.data.options;
 
-    match command.data.name.as_str().try_into().ok() {
+    if let Some(route) = &route {
+        let requirement = route.requirement(options);
+
+        let denied =
+            permission::check(ctx, command.member.as_deref(), guild_id, requirement).await?;
+
+        // 투표하는 서버에서는 권한이 없어도 투표해서 중지할 수 있음
+        let can_vote =
+            matches!(route, Route::Stop) && usecase::vote::is_enabled(ctx, guild_id).await?;
+
+        if let Some(denied) = denied.filter(|_| !can_vote) {
+            interaction
+                .send_ephemeral_message(&ctx.http, denied)
+                .await?;
+
+            return Ok(());
+        }
+    }
+
+    let history_channel_id = controller::get_history_channel_id(ctx, guild_id).await?;
+    let do_interact = Some(command.channel_id) != history_channel_id;
+
+    let typing = interaction.channel_id().start_typing(&ctx.http);
+
+    match route {
         Some(Route::Ping) => {
             interaction.send_message(&ctx.http, "pong").await?;
         }
 
         Some(Route::Play) => {
-            let parameter = controller::play::Parameter::from(options);
+            let parameter = controller::play::Parameter::from(&command.data);
 
             controller::play(ctx, interaction, parameter, do_interact).await?;
 
@@ -166,23 +354,76 @@ pub async fn route_application_command(
             controller::track(ctx, interaction).await?;
         }
 
+        Some(Route::Queue) => {
+            let parameter = controller::queue::Parameter::from(options);
+
+            controller::queue(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Skip) => {
+            controller::skip(ctx, interaction).await?;
+        }
+
+        Some(Route::Setup) => {
+            let parameter = controller::setup::Parameter::from(options);
+
+            controller::setup(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Join) => {
+            controller::join(ctx, interaction).await?;
+        }
+
+        Some(Route::Leave) => {
+            controller::leave(ctx, interaction).await?;
+        }
+
+        Some(Route::Pause) => {
+            controller::pause(ctx, interaction).await?;
+        }
+
+        Some(Route::Resume) => {
+            controller::resume(ctx, interaction).await?;
+        }
+
+        Some(Route::Seek) => {
+            let parameter = controller::seek::Parameter::from(options);
+
+            controller::seek(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::YtDlp) => {
+            let parameter = controller::ytdlp::Parameter::from(options);
+
+            controller::ytdlp(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Stats) => {
+            let parameter = controller::stats::Parameter::from(options);
+
+            controller::stats(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Filter) => {
+            let parameter = controller::filter::Parameter::from(options);
+
+            controller::filter(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Lyrics) => {
+            controller::lyrics(ctx, interaction).await?;
+        }
+
+        Some(Route::Config) => {
+            let parameter = controller::config::Parameter::from(options);
+
+            controller::config(ctx, interaction, parameter).await?;
+        }
+
         Some(Route::Search) => {
-            // TODO:
-            // 일단 검색엔진 뭐 쓸지 생각좀 하자
-            // 1. https://tantivy-search.github.io/examples/basic_search.html
-            //
-            //
-            // 그리고 어떻게 구현할지도 생각하자
-            //
-            // 버튼으로 페이지 구현 (수동으로 페이지 지정? ㄴㄴ하지말자 그냥 버튼만 ㄱㄱ)
-            //
-            //
-            // 검색결과는 어떻게 보여줄까
-            //
-            // [제목링크] [재생 버튼]
-            // 이런식으로 하면 될 듯?
-            //
-            // 커스텀 검색어 지정할 수 있도록
+            let parameter = controller::search::Parameter::from(options);
+
+            controller::search(ctx, interaction, parameter).await?;
         }
 
         Some(Route::UpdateScApiKey) => {
@@ -208,7 +449,7 @@ pub async fn route_application_command(
             if is_valid {
                 store
                     .elgua_cfg()
-                    .add_or_update(CfgKey::SoundCloudApiKey, sc_api_key)
+                    .add_or_update(CfgKey::SoundCloudApiKey, None, sc_api_key)
                     .await?;
 
                 interaction
@@ -229,6 +470,19 @@ pub async fn route_application_command(
     Ok(())
 }
 
+/// 명령어를 입력하는 동안 보여줄 추천
+pub async fn route_autocomplete(ctx: &Context, interaction: &Interaction) -> crate::Result<()> {
+    let Interaction::Autocomplete(command) = interaction else {
+        return Ok(());
+    };
+
+    if let Ok(Route::Play) = Route::try_from(command.data.name.as_str()) {
+        controller::autocomplete(ctx, interaction).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn route_message_component(
     ctx: &Context,
     interaction: &Interaction,
@@ -237,18 +491,32 @@ pub async fn route_message_component(
         return Ok(());
     };
 
-    let history_channel_id = ctx
-        .data
-        .read()
-        .await
-        .get::<Cfg>()
-        .unwrap()
-        .history_channel_id;
-    let do_interact = component.message.channel_id != history_channel_id;
+    let Some(guild_id) = component.guild_id else {
+        return Ok(());
+    };
+
+    let history_channel_id = controller::get_history_channel_id(ctx, guild_id).await?;
+    let do_interact = Some(component.message.channel_id) != history_channel_id;
+
+    let route = match component.data.custom_id.as_str().try_into().ok() {
+        Some(Route::PlayHistory(kind, uid)) => {
+            match usecase::play::find_url(ctx, kind, &uid).await? {
+                Some(url) => Some(Route::PlayFromClickedButton(url)),
+                None => {
+                    interaction
+                        .send_ephemeral_message(&ctx.http, "재생 기록을 찾을 수 없어요")
+                        .await?;
+
+                    return Ok(());
+                }
+            }
+        }
+        route => route,
+    };
 
     let typing = interaction.channel_id().start_typing(&ctx.http);
 
-    match component.data.custom_id.as_str().try_into().ok() {
+    match route {
         Some(Route::PlayFromSelectedMenu) => {
             let parameter = controller::play::Parameter::from(&component.data);
 
@@ -278,6 +546,24 @@ pub async fn route_message_component(
             // }
         }
 
+        Some(Route::SearchPage(page, keyword)) => {
+            let parameter = controller::search::Parameter::new(keyword, page);
+
+            controller::search(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::StatsPage(category, period, page)) => {
+            let parameter = controller::stats::Parameter::new(category, period, page);
+
+            controller::stats(ctx, interaction, parameter).await?;
+        }
+
+        Some(Route::Vote(vote_id, agree)) => {
+            let parameter = controller::vote::Parameter::new(vote_id, agree);
+
+            controller::vote(ctx, interaction, parameter).await?;
+        }
+
         _ => {}
     }
 
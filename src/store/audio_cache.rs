@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::audio::AudioSourceKind;
+
+#[derive(Debug, Clone)]
+pub struct AudioCacheEntry {
+    pub kind: AudioSourceKind,
+    pub id: String,
+    pub size: u64,
+    pub accessed_at: DateTime<Utc>,
+}
+
+impl From<AudioCacheRow> for AudioCacheEntry {
+    fn from(x: AudioCacheRow) -> Self {
+        Self {
+            kind: x.kind.into(),
+            id: x.id,
+            size: x.size as u64,
+            accessed_at: x.accessed_at,
+        }
+    }
+}
+
+pub struct AudioCacheStore {
+    conn: PgPool,
+}
+
+impl AudioCacheStore {
+    pub(super) fn new(conn: PgPool) -> Self {
+        Self { conn }
+    }
+
+    /// 재생할 때마다 마지막으로 사용한 시간을 갱신함
+    pub async fn touch(&self, entry: &AudioCacheEntry) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audio_cache (kind, id, size, accessed_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, id)
+            DO UPDATE
+                SET size = $3,
+                    accessed_at = $4
+            "#,
+        )
+        .bind(entry.kind.as_str())
+        .bind(&entry.id)
+        .bind(entry.size as i64)
+        .bind(entry.accessed_at)
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 이미 기록된 파일은 덮어쓰지 않음
+    pub async fn add_if_absent(&self, entry: &AudioCacheEntry) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audio_cache (kind, id, size, accessed_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, id)
+            DO NOTHING
+            "#,
+        )
+        .bind(entry.kind.as_str())
+        .bind(&entry.id)
+        .bind(entry.size as i64)
+        .bind(entry.accessed_at)
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 가장 오래 사용하지 않은 순서로 가져옴
+    pub async fn list(&self) -> sqlx::Result<Vec<AudioCacheEntry>> {
+        let entries = sqlx::query_as("SELECT * FROM audio_cache ORDER BY accessed_at ASC")
+            .fetch_all(&self.conn)
+            .await?
+            .into_iter()
+            .map(|x: AudioCacheRow| x.into())
+            .collect();
+
+        Ok(entries)
+    }
+
+    pub async fn total_size(&self) -> sqlx::Result<u64> {
+        let size: Option<i64> = sqlx::query_scalar("SELECT sum(size)::bigint FROM audio_cache")
+            .fetch_one(&self.conn)
+            .await?;
+
+        Ok(size.unwrap_or(0) as u64)
+    }
+
+    pub async fn remove(&self, kind: AudioSourceKind, id: &str) -> sqlx::Result<()> {
+        sqlx::query("DELETE FROM audio_cache WHERE kind = $1 AND id = $2")
+            .bind(kind.as_str())
+            .bind(id)
+            .execute(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AudioCacheRow {
+    kind: String,
+    id: String,
+    size: i64,
+    accessed_at: DateTime<Utc>,
+}
@@ -1,27 +1,167 @@
-use sqlx::{postgres::PgQueryResult, PgPool, Row};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use serenity::model::id::GuildId;
+use sqlx::{PgPool, Row};
+
+/// 실행 중에 바꿀 수 있는 설정
+///
+/// 새로운 설정은 variant를 추가하고 `ALL`과 아래 메서드에 이름, 설명, 기본값, 검증을 추가하면 됨
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CfgKey {
     SoundCloudApiKey,
+    DefaultVolume,
+    RepeatMaxMinutes,
+    PlayCountMax,
+    AdminRole,
+    DjRole,
+    Vote,
+    VoteRatio,
+    VoteTimeoutSecs,
 }
 
-impl From<String> for CfgKey {
-    fn from(x: String) -> Self {
-        match x.as_str() {
-            "soundcloudapikey" => Self::SoundCloudApiKey,
-            _ => unreachable!(),
-        }
+/// 설정을 적용하는 범위
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfgScope {
+    /// 모든 서버. `/config`로 바꿀 수 없음
+    Global,
+    /// 서버마다 따로 정함
+    Guild,
+}
+
+/// 재생 횟수를 정할 수 있는 최대값. 명령어를 등록할 때도 사용함
+pub const PLAY_COUNT_LIMIT: u64 = 10000;
+
+impl FromStr for CfgKey {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CfgKey::ALL.into_iter().find(|x| x.as_str() == s).ok_or(())
     }
 }
 
 impl CfgKey {
-    pub fn as_str(&self) -> &str {
+    pub const ALL: [CfgKey; 9] = [
+        CfgKey::SoundCloudApiKey,
+        CfgKey::DefaultVolume,
+        CfgKey::RepeatMaxMinutes,
+        CfgKey::PlayCountMax,
+        CfgKey::AdminRole,
+        CfgKey::DjRole,
+        CfgKey::Vote,
+        CfgKey::VoteRatio,
+        CfgKey::VoteTimeoutSecs,
+    ];
+
+    /// `/config`로 바꿀 수 있는 설정
+    pub fn guild_keys() -> impl Iterator<Item = CfgKey> {
+        Self::ALL
+            .into_iter()
+            .filter(|x| x.scope() == CfgScope::Guild)
+    }
+
+    pub fn as_str(&self) -> &'static str {
         use CfgKey::*;
 
         match self {
             SoundCloudApiKey => "soundcloudapikey",
+            DefaultVolume => "default_volume",
+            RepeatMaxMinutes => "repeat_max_minutes",
+            PlayCountMax => "play_count_max",
+            AdminRole => "admin_role",
+            DjRole => "dj_role",
+            Vote => "vote",
+            VoteRatio => "vote_ratio",
+            VoteTimeoutSecs => "vote_timeout_secs",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        use CfgKey::*;
+
+        match self {
+            SoundCloudApiKey => "SoundCloud Client ID",
+            DefaultVolume => "소리 크기를 정하지 않은 음악의 기본 소리 크기 (1 ~ 100)",
+            RepeatMaxMinutes => "반복 재생할 수 있는 음악의 최대 길이 (분)",
+            PlayCountMax => "정할 수 있는 최대 재생 횟수",
+            AdminRole => "봇의 설정을 바꿀 수 있는 역할",
+            DjRole => "다른 사람이 재생한 음악을 조작할 수 있는 역할",
+            Vote => "다른 사람이 재생한 음악을 중지하거나 바꿀 때 투표하기 (on, off)",
+            VoteRatio => "투표를 통과하려면 넘어야 하는 찬성 비율 (%)",
+            VoteTimeoutSecs => "투표를 끝내는 시간 (초)",
         }
     }
+
+    pub fn scope(&self) -> CfgScope {
+        match self {
+            Self::SoundCloudApiKey => CfgScope::Global,
+            _ => CfgScope::Guild,
+        }
+    }
+
+    /// 기본값이 없으면 시작할 때 받은 설정을 사용함
+    pub fn default_value(&self) -> Option<&'static str> {
+        use CfgKey::*;
+
+        match self {
+            SoundCloudApiKey | AdminRole | DjRole => None,
+            DefaultVolume => Some("5"),
+            RepeatMaxMinutes => Some("10"),
+            PlayCountMax => Some("523"),
+            Vote => Some("false"),
+            VoteRatio => Some("50"),
+            VoteTimeoutSecs => Some("60"),
+        }
+    }
+
+    /// 저장할 값으로 바꿈
+    pub fn validate(&self, value: &str) -> Result<String, String> {
+        use CfgKey::*;
+
+        let value = value.trim();
+
+        let range = match self {
+            SoundCloudApiKey if value.is_empty() => return Err("값을 입력해 주세요".to_string()),
+            SoundCloudApiKey => return Ok(value.to_string()),
+            AdminRole | DjRole => {
+                return parse_role_id(value)
+                    .ok_or_else(|| "역할을 멘션하거나 역할 ID를 입력해 주세요".to_string())
+            }
+            Vote => {
+                return match value.to_lowercase().as_str() {
+                    "on" | "true" => Ok("true".to_string()),
+                    "off" | "false" => Ok("false".to_string()),
+                    _ => Err("on 또는 off를 입력해 주세요".to_string()),
+                }
+            }
+            DefaultVolume => 1..=100,
+            RepeatMaxMinutes => 1..=24 * 60,
+            PlayCountMax => 1..=PLAY_COUNT_LIMIT,
+            VoteRatio => 1..=99,
+            VoteTimeoutSecs => 10..=600,
+        };
+
+        match value.parse::<u64>() {
+            Ok(x) if range.contains(&x) => Ok(x.to_string()),
+            _ => Err(format!(
+                "{} ~ {} 사이의 숫자를 입력해 주세요",
+                range.start(),
+                range.end()
+            )),
+        }
+    }
+}
+
+/// `<@&123>` 또는 `123`
+fn parse_role_id(value: &str) -> Option<String> {
+    let id = value
+        .strip_prefix("<@&")
+        .and_then(|x| x.strip_suffix('>'))
+        .unwrap_or(value);
+
+    id.parse::<u64>()
+        .ok()
+        .filter(|x| *x != 0)
+        .map(|x| x.to_string())
 }
 
 #[derive(Debug, Clone)]
@@ -34,34 +174,35 @@ pub struct CfgStore {
     conn: PgPool,
 }
 
-impl CfgStore {
-    pub(super) async fn init(conn: &PgPool) {
-        let _r: PgQueryResult = sqlx::query(
-            r#"CREATE TABLE IF NOT EXISTS elgua_cfg
-            (
-                key varchar PRIMARY KEY,
-                value varchar NOT NULL
-            )"#,
-        )
-        .execute(conn)
-        .await
-        .expect("create table elgua_cfg");
+/// 모든 서버에 적용하는 설정은 guild_id를 0으로 저장함
+fn scoped_guild_id(key: CfgKey, guild_id: Option<GuildId>) -> i64 {
+    match (key.scope(), guild_id) {
+        (CfgScope::Guild, Some(guild_id)) => guild_id.get() as i64,
+        _ => 0,
     }
+}
 
+impl CfgStore {
     pub(super) fn new(conn: PgPool) -> Self {
         Self { conn }
     }
 
-    pub async fn add_or_update(&self, key: CfgKey, value: impl AsRef<str>) -> crate::Result<()> {
+    pub async fn add_or_update(
+        &self,
+        key: CfgKey,
+        guild_id: Option<GuildId>,
+        value: impl AsRef<str>,
+    ) -> crate::Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO elgua_cfg (key, value)
-            VALUES ($1, $2)
-            ON CONFLICT (key)
+            INSERT INTO elgua_cfg (guild_id, key, value)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id, key)
             DO UPDATE
-                SET value = $2
+                SET value = $3
         "#,
         )
+        .bind(scoped_guild_id(key, guild_id))
         .bind(key.as_str())
         .bind(value.as_ref())
         .execute(&self.conn)
@@ -70,17 +211,74 @@ impl CfgStore {
         Ok(())
     }
 
-    pub async fn get(&self, key: CfgKey) -> crate::Result<Option<String>> {
+    pub async fn get(
+        &self,
+        key: CfgKey,
+        guild_id: Option<GuildId>,
+    ) -> crate::Result<Option<String>> {
         let r = sqlx::query(
             r#"
             SELECT * FROM elgua_cfg
-            WHERE key = $1
+            WHERE guild_id = $1 AND
+                  key = $2
         "#,
         )
+        .bind(scoped_guild_id(key, guild_id))
         .bind(key.as_str())
         .fetch_optional(&self.conn)
         .await?;
 
         Ok(r.and_then(|x| x.try_get("value").ok()))
     }
+
+    /// 저장된 값이 없으면 기본값을 사용함
+    pub async fn get_or_default<T: FromStr>(
+        &self,
+        key: CfgKey,
+        guild_id: Option<GuildId>,
+    ) -> crate::Result<Option<T>> {
+        let value = self.get(key, guild_id).await?;
+
+        Ok(value
+            .and_then(|x| x.parse().ok())
+            .or_else(|| key.default_value()?.parse().ok()))
+    }
+
+    /// 기본값으로 되돌림
+    pub async fn remove(&self, key: CfgKey, guild_id: Option<GuildId>) -> crate::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM elgua_cfg
+            WHERE guild_id = $1 AND
+                  key = $2
+        "#,
+        )
+        .bind(scoped_guild_id(key, guild_id))
+        .bind(key.as_str())
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_cfg_key() {
+    for key in CfgKey::ALL {
+        assert_eq!(key.as_str().parse(), Ok(key));
+
+        if let Some(default_value) = key.default_value() {
+            assert_eq!(key.validate(default_value).as_deref(), Ok(default_value));
+        }
+    }
+
+    assert_eq!(CfgKey::DefaultVolume.validate(" 30 ").as_deref(), Ok("30"));
+    assert!(CfgKey::DefaultVolume.validate("0").is_err());
+    assert!(CfgKey::PlayCountMax.validate("many").is_err());
+    assert_eq!(
+        CfgKey::DjRole.validate("<@&123456>").as_deref(),
+        Ok("123456")
+    );
+    assert!(CfgKey::AdminRole.validate("@everyone").is_err());
+    assert_eq!(CfgKey::Vote.validate("ON").as_deref(), Ok("true"));
 }
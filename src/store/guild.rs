@@ -0,0 +1,97 @@
+use serenity::model::id::{ChannelId, GuildId};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct GuildCfg {
+    pub guild_id: GuildId,
+    pub voice_channel_id: Option<ChannelId>,
+    pub history_channel_id: Option<ChannelId>,
+}
+
+impl GuildCfg {
+    pub fn new(guild_id: GuildId) -> Self {
+        Self {
+            guild_id,
+            voice_channel_id: None,
+            history_channel_id: None,
+        }
+    }
+}
+
+impl From<GuildCfgRow> for GuildCfg {
+    fn from(x: GuildCfgRow) -> Self {
+        Self {
+            guild_id: GuildId::new(x.guild_id as u64),
+            voice_channel_id: x.voice_channel_id.map(|x| ChannelId::new(x as u64)),
+            history_channel_id: x.history_channel_id.map(|x| ChannelId::new(x as u64)),
+        }
+    }
+}
+
+pub struct GuildCfgStore {
+    conn: PgPool,
+}
+
+impl GuildCfgStore {
+    pub(super) fn new(conn: PgPool) -> Self {
+        Self { conn }
+    }
+
+    pub async fn add_or_update(&self, guild_cfg: &GuildCfg) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_cfg (guild_id, voice_channel_id, history_channel_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id)
+            DO UPDATE
+                SET voice_channel_id = $2,
+                    history_channel_id = $3
+            "#,
+        )
+        .bind(guild_cfg.guild_id.get() as i64)
+        .bind(guild_cfg.voice_channel_id.map(|x| x.get() as i64))
+        .bind(guild_cfg.history_channel_id.map(|x| x.get() as i64))
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 이미 설정된 서버는 덮어쓰지 않음
+    pub async fn add_if_absent(&self, guild_cfg: &GuildCfg) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO guild_cfg (guild_id, voice_channel_id, history_channel_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id)
+            DO NOTHING
+            "#,
+        )
+        .bind(guild_cfg.guild_id.get() as i64)
+        .bind(guild_cfg.voice_channel_id.map(|x| x.get() as i64))
+        .bind(guild_cfg.history_channel_id.map(|x| x.get() as i64))
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 설정되지 않은 서버는 빈 설정을 반환함
+    pub async fn get(&self, guild_id: GuildId) -> sqlx::Result<GuildCfg> {
+        let guild_cfg = sqlx::query_as("SELECT * FROM guild_cfg WHERE guild_id = $1")
+            .bind(guild_id.get() as i64)
+            .fetch_optional(&self.conn)
+            .await?
+            .map(|x: GuildCfgRow| x.into())
+            .unwrap_or_else(|| GuildCfg::new(guild_id));
+
+        Ok(guild_cfg)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct GuildCfgRow {
+    guild_id: i64,
+    voice_channel_id: Option<i64>,
+    history_channel_id: Option<i64>,
+}
@@ -1,69 +1,52 @@
 use chrono::{DateTime, Utc};
-use sqlx::{postgres::PgQueryResult, PgPool, Row};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
 
 use crate::audio::AudioSourceKind;
 
-#[derive(Debug, Clone, Copy)]
-pub enum HistoryKind {
-    YouTube,
-    SoundCloud,
-}
-
-impl From<String> for HistoryKind {
-    fn from(x: String) -> Self {
-        match x.as_str() {
-            "youtube" => Self::YouTube,
-            "soundcloud" => Self::SoundCloud,
-            _ => unreachable!(),
-        }
-    }
-}
-
-impl From<AudioSourceKind> for HistoryKind {
-    fn from(x: AudioSourceKind) -> Self {
-        match x {
-            AudioSourceKind::YouTube => Self::YouTube,
-            AudioSourceKind::SoundCloud => Self::SoundCloud,
-        }
-    }
-}
-
-impl HistoryKind {
-    pub fn as_str(&self) -> &str {
-        use HistoryKind::*;
-
-        match self {
-            YouTube => "youtube",
-            SoundCloud => "soundcloud",
-        }
-    }
-}
+use super::search;
 
 #[derive(Debug, Clone)]
 pub struct History {
     pub id: u64,
+    /// 서버마다 따로 기록하기 전의 재생 기록 메세지. [`GuildHistory`]를 사용함
     pub message_id: Option<u64>,
     pub title: String,
     pub channel: String,
-    pub kind: HistoryKind,
+    pub kind: AudioSourceKind,
     pub uid: String,
+    /// 재생한 주소. 이 열을 추가하기 전의 기록이면 None
+    pub url: Option<String>,
     pub user_id: u64,
-    pub volume: u8,
+    /// 서버마다 따로 기록하기 전의 소리 크기. [`GuildHistory`]를 사용함
+    pub volume: Option<u8>,
     pub created_at: DateTime<Utc>,
 }
 
-/* impl From<History> for search::History {
-    fn from(x: History) -> Self {
+/// 서버마다 다른 재생 기록 메세지와 소리 크기
+#[derive(Debug, Clone, Default)]
+pub struct GuildHistory {
+    /// 재생 기록 메세지를 보낸 채널
+    pub message_channel_id: Option<u64>,
+    pub message_id: Option<u64>,
+    /// 소리 크기를 정하고 재생한 적이 없으면 None
+    pub volume: Option<u8>,
+}
+
+impl From<IndexedHistoryRow> for search::History {
+    fn from(IndexedHistoryRow { history, guild_ids }: IndexedHistoryRow) -> Self {
+        let x = History::from(history);
+
         Self {
             id: x.id,
             title: x.title,
             kind: x.kind.as_str().to_string(),
             channel: x.channel,
             user_id: x.user_id,
-            created_at: x.created_at.to_rfc3339(),
+            guild_ids: guild_ids.into_iter().map(|x| x as u64).collect(),
         }
     }
-} */
+}
 
 impl From<HistoryRow> for History {
     fn from(x: HistoryRow) -> Self {
@@ -74,44 +57,113 @@ impl From<HistoryRow> for History {
             channel: x.channel,
             kind: x.kind.into(),
             uid: x.uid,
+            url: x.url,
             user_id: x.user_id as u64,
-            volume: x.volume as u8,
+            volume: x.volume.map(|x| x as u8),
             created_at: x.created_at,
         }
     }
 }
 
+/// 재생할 때마다 남기는 기록
+///
+/// guild_id, track_uuid는 plays 테이블을 만들기 전의 기록이면 None
+#[derive(Debug, Clone)]
+pub struct Play {
+    pub id: u64,
+    pub history_id: u64,
+    pub guild_id: Option<u64>,
+    pub user_id: u64,
+    pub volume: u8,
+    /// 반복 재생 횟수
+    pub play_count: Option<usize>,
+    pub track_uuid: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    /// 재생 중이거나 끝난 시간을 기록하지 못했으면 None
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<PlayRow> for Play {
+    fn from(x: PlayRow) -> Self {
+        Self {
+            id: x.id as u64,
+            history_id: x.history_id as u64,
+            guild_id: x.guild_id.map(|x| x as u64),
+            user_id: x.user_id as u64,
+            volume: x.volume as u8,
+            play_count: x.play_count.map(|x| x as usize),
+            track_uuid: x.track_uuid,
+            started_at: x.started_at,
+            ended_at: x.ended_at,
+        }
+    }
+}
+
 pub struct HistoryStore {
     conn: PgPool,
-    // search: search::HistoryStore,
+    search: search::HistoryStore,
 }
 
 impl HistoryStore {
-    pub(super) async fn init(conn: &PgPool) {
-        let _r: PgQueryResult = sqlx::query(
-            r#"CREATE TABLE IF NOT EXISTS history
-            (
-                id bigserial PRIMARY KEY,
-                message_id bigint,
-                title varchar NOT NULL,
-                channel varchar NOT NULL,
-                kind varchar NOT NULL,
-                uid varchar NOT NULL,
-                user_id bigint NOT NULL,
-                volume smallint NOT NULL,
-                created_at timestamptz NOT NULL
-            )"#,
-        )
-        .execute(conn)
-        .await
-        .expect("create table history");
+    pub(super) fn new(conn: PgPool, search: search::HistoryStore) -> Self {
+        Self { conn, search }
     }
 
-    pub(super) fn new(conn: PgPool /* , toshi: toshi::ToshiClient */) -> Self {
-        Self {
-            conn,
-            // search: search::HistoryStore::new(toshi),
+    /// 색인된 문서 수가 기록 수와 다르면 색인을 다시 만듦
+    ///
+    /// 색인 디렉터리가 지워졌거나 색인하지 못한 기록이 있을 때
+    pub(super) async fn reindex_if_needed(&self) -> crate::Result<()> {
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM history")
+            .fetch_one(&self.conn)
+            .await?;
+
+        if count as u64 == self.search.len() {
+            return Ok(());
         }
+
+        self.reindex().await
+    }
+
+    /// 색인을 처음부터 다시 만듦
+    ///
+    /// 재생한 서버가 바뀌었을 때도 사용함
+    pub async fn reindex(&self) -> crate::Result<()> {
+        let histories: Vec<search::History> =
+            sqlx::query_as(&format!("{INDEXED_HISTORY} GROUP BY history.id"))
+                .fetch_all(&self.conn)
+                .await?
+                .into_iter()
+                .map(|x: IndexedHistoryRow| x.into())
+                .collect();
+
+        log::info!("reindexing {} histories", histories.len());
+
+        let search = self.search.clone();
+
+        tokio::task::spawn_blocking(move || search.add_all(&histories))
+            .await
+            .unwrap()?;
+
+        Ok(())
+    }
+
+    /// 기록 하나를 다시 색인함
+    async fn index(&self, history_id: u64) -> crate::Result<()> {
+        let x: IndexedHistoryRow = sqlx::query_as(&format!(
+            "{INDEXED_HISTORY} WHERE history.id = $1 GROUP BY history.id"
+        ))
+        .bind(history_id as i64)
+        .fetch_one(&self.conn)
+        .await?;
+
+        let x = search::History::from(x);
+        let search = self.search.clone();
+
+        tokio::task::spawn_blocking(move || search.add(&x))
+            .await
+            .unwrap()?;
+
+        Ok(())
     }
 
     pub async fn add_or_update(&self, history: &History) -> crate::Result<u64> {
@@ -122,16 +174,17 @@ impl HistoryStore {
 
         let r = sqlx::query(
             r#"
-            INSERT INTO history (title, channel, kind, uid, user_id, volume, created_at, message_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (uid)
+            INSERT INTO history (title, channel, kind, uid, user_id, volume, created_at, message_id, url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (kind, uid)
             DO UPDATE
                 SET title = $1,
                     channel = $2,
                     user_id = $5,
-                    volume = $6,
+                    volume = coalesce($6, history.volume),
                     created_at = $7,
-                    message_id = $8
+                    message_id = coalesce($8, history.message_id),
+                    url = coalesce($9, history.url)
             RETURNING id
             "#,
         )
@@ -140,34 +193,22 @@ impl HistoryStore {
         .bind(history.kind.as_str())
         .bind(history.uid.as_str())
         .bind(history.user_id as i64)
-        .bind(history.volume as i16)
+        .bind(history.volume.map(|x| x as i16))
         .bind(history.created_at)
         .bind(history.message_id.map(|x| x as i64))
+        .bind(history.url.as_deref())
         .fetch_one(&mut *conn)
         .await?;
 
         let id: i64 = r.try_get("id")?;
 
-        /* {
-            let x = History {
-                id: id as u64,
-                ..history.clone()
-            };
-
-            self.search.add(&x.into()).await?;
-        } */
-
         conn.commit().await?;
 
+        self.index(id as u64).await?;
+
         Ok(id as u64)
     }
 
-    // TODO: 여러가지
-    // 1. 노래들 중복 여부
-    // 2. 특정 유저
-    // 3. 특정 채널
-    // 4. 특정 kind
-
     pub async fn get(
         &self,
         page: usize,
@@ -183,7 +224,7 @@ impl HistoryStore {
                 };
                 format!(
                     r#"
-                SELECT id, title, channel, kind, uid, user_id, volume, created_at FROM history
+                SELECT id, title, channel, kind, uid, url, user_id, volume, created_at FROM history
                 WHERE {}
                 ORDER BY created_at DESC
                 OFFSET $1
@@ -193,7 +234,7 @@ impl HistoryStore {
             }
 
             None => r#"
-            SELECT id, title, channel, kind, uid, user_id, volume, created_at FROM history
+            SELECT id, title, channel, kind, uid, url, user_id, volume, created_at FROM history
             ORDER BY created_at DESC
             OFFSET $1
             LIMIT $2
@@ -215,7 +256,7 @@ impl HistoryStore {
 
     pub async fn find_one(
         &self,
-        kind: HistoryKind,
+        kind: AudioSourceKind,
         uid: impl AsRef<str>,
     ) -> sqlx::Result<Option<History>> {
         let sql = r#"
@@ -251,41 +292,311 @@ impl HistoryStore {
         Ok(history)
     }
 
-    pub async fn update_volume(
+    pub async fn get_guild_history(
         &self,
-        kind: HistoryKind,
-        uid: &str,
+        guild_id: u64,
+        history_id: u64,
+    ) -> sqlx::Result<Option<GuildHistory>> {
+        let sql = r#"
+            SELECT message_channel_id, message_id, volume FROM guild_history
+            WHERE guild_id = $1 AND
+                  history_id = $2
+        "#;
+
+        let r = sqlx::query(sql)
+            .bind(guild_id as i64)
+            .bind(history_id as i64)
+            .fetch_optional(&self.conn)
+            .await?;
+
+        let Some(r) = r else {
+            return Ok(None);
+        };
+
+        Ok(Some(GuildHistory {
+            message_channel_id: r
+                .try_get::<Option<i64>, _>("message_channel_id")?
+                .map(|x| x as u64),
+            message_id: r.try_get::<Option<i64>, _>("message_id")?.map(|x| x as u64),
+            volume: r.try_get::<Option<i16>, _>("volume")?.map(|x| x as u8),
+        }))
+    }
+
+    /// 재생 기록 메세지를 보내지 않았으면 message는 None
+    pub async fn update_guild_message(
+        &self,
+        guild_id: u64,
+        history_id: u64,
+        message: Option<(u64, u64)>,
+    ) -> sqlx::Result<()> {
+        let sql = r#"
+            INSERT INTO guild_history (guild_id, history_id, message_channel_id, message_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (guild_id, history_id)
+            DO UPDATE
+                SET message_channel_id = $3,
+                    message_id = $4
+        "#;
+
+        sqlx::query(sql)
+            .bind(guild_id as i64)
+            .bind(history_id as i64)
+            .bind(message.map(|(channel_id, _)| channel_id as i64))
+            .bind(message.map(|(_, message_id)| message_id as i64))
+            .execute(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_guild_volume(
+        &self,
+        guild_id: u64,
+        history_id: u64,
         volume: u8,
     ) -> sqlx::Result<()> {
         let sql = r#"
-            UPDATE history
-            SET volume = $1
-            WHERE uid = $2 AND kind = $3
+            INSERT INTO guild_history (guild_id, history_id, volume)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id, history_id)
+            DO UPDATE
+                SET volume = $3
         "#;
 
         sqlx::query(sql)
+            .bind(guild_id as i64)
+            .bind(history_id as i64)
             .bind(volume as i16)
-            .bind(uid)
-            .bind(kind.as_str())
             .execute(&self.conn)
             .await?;
 
         Ok(())
     }
 
-    pub async fn search() {}
+    /// 제목에 keyword가 들어간 기록. 제목이 keyword로 시작하는 기록을 먼저 찾음
+    ///
+    /// 입력하는 중인 단어도 찾을 수 있도록 검색 색인 대신 사용함
+    pub async fn find_by_title(
+        &self,
+        keyword: &str,
+        guild_id: u64,
+        limit: usize,
+    ) -> sqlx::Result<Vec<History>> {
+        let keyword = keyword
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        let sql = r#"
+            SELECT * FROM history
+            WHERE title ILIKE $1 AND
+                  EXISTS (SELECT 1 FROM plays WHERE plays.history_id = history.id AND plays.guild_id = $3)
+            ORDER BY title ILIKE $2 DESC,
+                     created_at DESC
+            LIMIT $4
+        "#;
+
+        let histories = sqlx::query_as(sql)
+            .bind(format!("%{keyword}%"))
+            .bind(format!("{keyword}%"))
+            .bind(guild_id as i64)
+            .bind(limit as i64)
+            .fetch_all(&self.conn)
+            .await?
+            .into_iter()
+            .map(|x: HistoryRow| x.into())
+            .collect();
+
+        Ok(histories)
+    }
+
+    /// 제목, 채널, 종류로 검색하고 `<@유저 ID>`로 재생한 유저를 지정할 수 있음
+    ///
+    /// returns (histories, total)
+    pub async fn search(
+        &self,
+        keyword: &str,
+        guild_id: u64,
+        page: usize,
+        per_page: usize,
+    ) -> crate::Result<(Vec<History>, usize)> {
+        let search = self.search.clone();
+        let keyword = keyword.to_string();
+
+        let (ids, total) = tokio::task::spawn_blocking(move || {
+            search.search(&keyword, guild_id, (page - 1) * per_page, per_page)
+        })
+        .await
+        .unwrap()?;
+
+        let ids = ids.into_iter().map(|x| x as i64).collect::<Vec<_>>();
+
+        let mut histories: Vec<History> =
+            sqlx::query_as("SELECT * FROM history WHERE id = ANY($1)")
+                .bind(&ids)
+                .fetch_all(&self.conn)
+                .await?
+                .into_iter()
+                .map(|x: HistoryRow| x.into())
+                .collect();
+
+        // 검색 결과의 순서대로 정렬함
+        histories.sort_by_key(|x| ids.iter().position(|id| *id as u64 == x.id));
+
+        Ok((histories, total))
+    }
+
+    /// 처음 재생한 서버에서도 검색할 수 있도록 다시 색인함
+    pub async fn add_play(&self, play: &Play) -> crate::Result<u64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO plays (history_id, guild_id, user_id, volume, play_count, track_uuid, started_at, ended_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(play.history_id as i64)
+        .bind(play.guild_id.map(|x| x as i64))
+        .bind(play.user_id as i64)
+        .bind(play.volume as i16)
+        .bind(play.play_count.map(|x| x as i32))
+        .bind(play.track_uuid)
+        .bind(play.started_at)
+        .bind(play.ended_at)
+        .fetch_one(&self.conn)
+        .await?;
+
+        self.index(play.history_id).await?;
+
+        Ok(id as u64)
+    }
+
+    /// 재생이 끝나거나 중지된 시간을 기록함
+    pub async fn end_play(&self, track_uuid: Uuid, ended_at: DateTime<Utc>) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE plays
+            SET ended_at = $2
+            WHERE track_uuid = $1 AND
+                  ended_at IS NULL
+            "#,
+        )
+        .bind(track_uuid)
+        .bind(ended_at)
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 필터를 바꾸거나 재생 위치를 옮겨서 다시 재생한 음악을 같은 재생 기록으로 이어감
+    pub async fn replace_track_uuid(
+        &self,
+        track_uuid: Uuid,
+        new_track_uuid: Uuid,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE plays
+            SET track_uuid = $2
+            WHERE track_uuid = $1 AND
+                  ended_at IS NULL
+            "#,
+        )
+        .bind(track_uuid)
+        .bind(new_track_uuid)
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn play_count(&self, kind: AudioSourceKind, uid: &str) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT count(*) FROM plays
+            INNER JOIN history ON history.id = plays.history_id
+            WHERE history.kind = $1 AND
+                  history.uid = $2
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(uid)
+        .fetch_one(&self.conn)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    /// 최근에 재생한 순서로 limit개
+    pub async fn recent_plays(
+        &self,
+        kind: AudioSourceKind,
+        uid: &str,
+        limit: usize,
+    ) -> sqlx::Result<Vec<Play>> {
+        let plays = sqlx::query_as(
+            r#"
+            SELECT plays.* FROM plays
+            INNER JOIN history ON history.id = plays.history_id
+            WHERE history.kind = $1 AND
+                  history.uid = $2
+            ORDER BY plays.started_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(uid)
+        .bind(limit as i64)
+        .fetch_all(&self.conn)
+        .await?
+        .into_iter()
+        .map(|x: PlayRow| x.into())
+        .collect();
+
+        Ok(plays)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PlayRow {
+    id: i64,
+    history_id: i64,
+    guild_id: Option<i64>,
+    user_id: i64,
+    volume: i16,
+    play_count: Option<i32>,
+    track_uuid: Option<Uuid>,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
 }
 
+/// 기록과 재생한 서버를 함께 가져옴
+const INDEXED_HISTORY: &str = r#"
+    SELECT history.*,
+           coalesce(array_agg(DISTINCT plays.guild_id) FILTER (WHERE plays.guild_id IS NOT NULL), '{}') AS guild_ids
+    FROM history
+    LEFT JOIN plays ON plays.history_id = history.id
+"#;
+
 #[derive(sqlx::FromRow)]
-struct HistoryRow {
+struct IndexedHistoryRow {
+    #[sqlx(flatten)]
+    history: HistoryRow,
+    guild_ids: Vec<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct HistoryRow {
     id: i64,
     message_id: Option<i64>,
     title: String,
     channel: String,
     kind: String,
     uid: String,
+    url: Option<String>,
     user_id: i64,
-    volume: i16,
+    volume: Option<i16>,
     created_at: DateTime<Utc>,
 }
 
@@ -0,0 +1,44 @@
+use sqlx::PgPool;
+
+use crate::audio::AudioSourceKind;
+
+/// 음악마다 측정한 음량 (LUFS)
+///
+/// 무음이라 측정할 수 없는 음악은 기록하지 않음
+pub struct LoudnessStore {
+    conn: PgPool,
+}
+
+impl LoudnessStore {
+    pub(super) fn new(conn: PgPool) -> Self {
+        Self { conn }
+    }
+
+    pub async fn get(&self, kind: AudioSourceKind, uid: &str) -> sqlx::Result<Option<f64>> {
+        sqlx::query_scalar("SELECT integrated FROM loudness WHERE kind = $1 AND uid = $2")
+            .bind(kind.as_str())
+            .bind(uid)
+            .fetch_optional(&self.conn)
+            .await
+    }
+
+    pub async fn set(&self, kind: AudioSourceKind, uid: &str, integrated: f64) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO loudness (kind, uid, integrated, measured_at)
+            VALUES ($1, $2, $3, now())
+            ON CONFLICT (kind, uid)
+            DO UPDATE
+                SET integrated = $3,
+                    measured_at = now()
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(uid)
+        .bind(integrated)
+        .execute(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+}
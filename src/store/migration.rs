@@ -0,0 +1,111 @@
+//! 데이터베이스 스키마를 버전별로 관리함
+//!
+//! 적용한 버전은 schema_version 테이블에 기록하고 적용하지 않은 마이그레이션만 순서대로 실행함.
+//! 이미 적용한 마이그레이션은 수정하지 말고 새로운 마이그레이션을 추가해야 함
+
+use sqlx::PgPool;
+
+struct Migration {
+    version: i32,
+    description: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "init",
+        sql: include_str!("migrations/0001_init.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "dedupe history",
+        sql: include_str!("migrations/0002_dedupe_history.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "history kind uid unique",
+        sql: include_str!("migrations/0003_history_kind_uid_unique.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "plays",
+        sql: include_str!("migrations/0004_plays.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "history url",
+        sql: include_str!("migrations/0005_history_url.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "loudness",
+        sql: include_str!("migrations/0006_loudness.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "guild elgua cfg",
+        sql: include_str!("migrations/0007_guild_elgua_cfg.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "guild history",
+        sql: include_str!("migrations/0008_guild_history.sql"),
+    },
+];
+
+/// 여러 프로세스가 동시에 시작해도 마이그레이션은 한 번만 실행되도록 잠금
+const LOCK_ID: i64 = 0x656c677561;
+
+pub(super) async fn run(conn: &PgPool) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS schema_version
+        (
+            version integer PRIMARY KEY,
+            description varchar NOT NULL,
+            applied_at timestamptz NOT NULL DEFAULT now()
+        )"#,
+    )
+    .execute(conn)
+    .await?;
+
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(LOCK_ID)
+        .execute(&mut *tx)
+        .await?;
+
+    let current_version: i32 =
+        sqlx::query_scalar("SELECT coalesce(max(version), 0) FROM schema_version")
+            .fetch_one(&mut *tx)
+            .await?;
+
+    // 하나라도 실패하면 모두 되돌림
+    for migration in MIGRATIONS.iter().filter(|x| x.version > current_version) {
+        log::info!(
+            "applying migration {}: {}",
+            migration.version,
+            migration.description
+        );
+
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_migration_versions() {
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version, i as i32 + 1);
+    }
+}
@@ -0,0 +1,52 @@
+-- 마이그레이션을 도입하기 전에 각 저장소에서 만들던 테이블
+-- 이미 만들어진 데이터베이스에서도 실행할 수 있어야 함
+
+CREATE TABLE IF NOT EXISTS audio_cache
+(
+    kind varchar NOT NULL,
+    id varchar NOT NULL,
+    size bigint NOT NULL,
+    accessed_at timestamptz NOT NULL,
+    PRIMARY KEY (kind, id)
+);
+
+CREATE TABLE IF NOT EXISTS elgua_cfg
+(
+    key varchar PRIMARY KEY,
+    value varchar NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS guild_cfg
+(
+    guild_id bigint PRIMARY KEY,
+    voice_channel_id bigint,
+    history_channel_id bigint
+);
+
+CREATE TABLE IF NOT EXISTS history
+(
+    id bigserial PRIMARY KEY,
+    message_id bigint,
+    title varchar NOT NULL,
+    channel varchar NOT NULL,
+    kind varchar NOT NULL,
+    uid varchar NOT NULL,
+    user_id bigint NOT NULL,
+    volume smallint NOT NULL,
+    created_at timestamptz NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS track_queue
+(
+    id bigserial PRIMARY KEY,
+    guild_id bigint NOT NULL,
+    position bigint NOT NULL,
+    title varchar NOT NULL,
+    kind varchar NOT NULL,
+    uid varchar NOT NULL,
+    url varchar NOT NULL,
+    user_id bigint NOT NULL,
+    volume smallint,
+    play_count integer,
+    created_at timestamptz NOT NULL
+);
@@ -0,0 +1,8 @@
+-- uid에 unique 제약이 없어서 ON CONFLICT (uid)가 실패하거나 같은 음악이 여러 번 기록됨
+-- 같은 음악의 기록은 가장 최근에 재생한 기록만 남김
+
+DELETE FROM history AS h
+USING history AS newer
+WHERE h.kind = newer.kind
+  AND h.uid = newer.uid
+  AND (h.created_at, h.id) < (newer.created_at, newer.id);
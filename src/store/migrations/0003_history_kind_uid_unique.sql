@@ -0,0 +1,3 @@
+-- 유튜브와 사운드클라우드의 uid가 겹쳐도 다른 음악으로 기록함
+
+CREATE UNIQUE INDEX IF NOT EXISTS history_kind_uid_key ON history (kind, uid);
@@ -0,0 +1,22 @@
+-- history는 음악마다 마지막으로 재생한 기록만 남기기 때문에 재생할 때마다 따로 기록함
+
+CREATE TABLE IF NOT EXISTS plays
+(
+    id bigserial PRIMARY KEY,
+    history_id bigint NOT NULL REFERENCES history (id) ON DELETE CASCADE,
+    -- 이 테이블을 만들기 전에 재생한 기록은 서버와 트랙을 알 수 없음
+    guild_id bigint,
+    user_id bigint NOT NULL,
+    volume smallint NOT NULL,
+    play_count integer,
+    track_uuid uuid UNIQUE,
+    started_at timestamptz NOT NULL,
+    ended_at timestamptz
+);
+
+CREATE INDEX IF NOT EXISTS plays_history_id_started_at_idx ON plays (history_id, started_at DESC);
+
+CREATE INDEX IF NOT EXISTS plays_started_at_idx ON plays (started_at);
+
+INSERT INTO plays (history_id, user_id, volume, started_at)
+SELECT id, user_id, volume, created_at FROM history;
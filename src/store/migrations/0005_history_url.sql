@@ -0,0 +1,4 @@
+-- id로 주소를 만들 수 없는 곳에서 가져온 음악은 기록된 주소로 다시 재생함
+-- 이전 기록은 id로 주소를 만들 수 있으므로 비워 둠
+
+ALTER TABLE history ADD COLUMN IF NOT EXISTS url varchar;
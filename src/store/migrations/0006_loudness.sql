@@ -0,0 +1,13 @@
+-- 소리 크기를 정하지 않고 재생한 음악은 기록하지 않고 측정한 음량으로 소리 크기를 정함
+-- 이전 기록의 소리 크기는 그대로 사용함
+
+ALTER TABLE history ALTER COLUMN volume DROP NOT NULL;
+
+CREATE TABLE IF NOT EXISTS loudness
+(
+    kind varchar NOT NULL,
+    uid varchar NOT NULL,
+    integrated double precision NOT NULL,
+    measured_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (kind, uid)
+);
@@ -0,0 +1,5 @@
+-- 서버마다 다르게 정할 수 있는 설정. 0은 모든 서버에 적용하는 설정
+ALTER TABLE elgua_cfg ADD COLUMN IF NOT EXISTS guild_id bigint NOT NULL DEFAULT 0;
+
+ALTER TABLE elgua_cfg DROP CONSTRAINT IF EXISTS elgua_cfg_pkey;
+ALTER TABLE elgua_cfg ADD PRIMARY KEY (guild_id, key);
@@ -0,0 +1,29 @@
+-- history는 음악마다 하나라서 여러 서버에서 재생하면 재생 기록 메세지와 소리 크기가 섞임
+-- 서버마다 따로 기록하고 history의 message_id, volume은 더 이상 사용하지 않음
+
+CREATE TABLE IF NOT EXISTS guild_history
+(
+    guild_id bigint NOT NULL,
+    history_id bigint NOT NULL REFERENCES history (id) ON DELETE CASCADE,
+    -- 재생 기록 메세지를 보낸 채널
+    message_channel_id bigint,
+    message_id bigint,
+    volume smallint,
+    PRIMARY KEY (guild_id, history_id)
+);
+
+-- 이전 기록은 마지막으로 재생한 서버의 기록으로 옮김
+-- 서버를 알 수 없는 재생 기록은 `claim_unknown_guild`에서 서버를 정할 때 옮김
+INSERT INTO guild_history (guild_id, history_id, message_channel_id, message_id, volume)
+SELECT DISTINCT ON (history.id)
+       plays.guild_id,
+       history.id,
+       guild_cfg.history_channel_id,
+       history.message_id,
+       history.volume
+FROM history
+JOIN plays ON plays.history_id = history.id AND plays.guild_id IS NOT NULL
+LEFT JOIN guild_cfg ON guild_cfg.guild_id = plays.guild_id
+WHERE history.message_id IS NOT NULL OR history.volume IS NOT NULL
+ORDER BY history.id, plays.started_at DESC
+ON CONFLICT DO NOTHING;
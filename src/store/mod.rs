@@ -3,19 +3,32 @@ use sqlx::{postgres::PgPoolOptions, PgPool};
 
 use crate::cfg::Cfg;
 
-use self::{cfg::CfgStore, history::HistoryStore};
+use self::{
+    audio_cache::AudioCacheStore, cfg::CfgStore, guild::GuildCfgStore, history::HistoryStore,
+    loudness::LoudnessStore, stats::StatsStore, track_queue::TrackQueueStore,
+};
 
+mod audio_cache;
 mod cfg;
+mod guild;
 mod history;
-// mod search;
-// mod track_queue;
+mod loudness;
+mod migration;
+mod search;
+mod stats;
+mod track_queue;
 
-pub use cfg::{CfgKey, ElguaCfg};
-pub use history::{History, HistoryKind};
+pub use audio_cache::AudioCacheEntry;
+pub use cfg::{CfgKey, CfgScope, ElguaCfg, PLAY_COUNT_LIMIT};
+pub use guild::GuildCfg;
+pub use history::{GuildHistory, History, Play};
+pub use search::HISTORY_INDEX;
+pub use stats::Ranking;
+pub use track_queue::{TrackQueue, MAX_LEN as TRACK_QUEUE_MAX_LEN};
 
 pub struct Store {
     connection: PgPool,
-    // toshi_url: String,
+    search: search::HistoryStore,
 }
 
 impl Store {
@@ -25,24 +38,48 @@ impl Store {
             .await
             .expect("connect pg");
 
-        CfgStore::init(&pg_pool).await;
-        HistoryStore::init(&pg_pool).await;
-        // search::HistoryStore::init(&cfg.toshi_url).await;
+        migration::run(&pg_pool).await.expect("migrate");
+
+        let search = search::HistoryStore::init(HISTORY_INDEX).expect("open history index");
+
+        HistoryStore::new(pg_pool.clone(), search.clone())
+            .reindex_if_needed()
+            .await
+            .expect("reindex history");
 
         Self {
             connection: pg_pool,
-            // toshi_url: cfg.toshi_url.clone(),
+            search,
         }
     }
 
     pub fn history(&self) -> HistoryStore {
-        // let toshi = toshi::ToshiClient::new(&self.toshi_url);
-        HistoryStore::new(self.connection.clone() /* , toshi */)
+        HistoryStore::new(self.connection.clone(), self.search.clone())
+    }
+
+    pub fn audio_cache(&self) -> AudioCacheStore {
+        AudioCacheStore::new(self.connection.clone())
     }
 
     pub fn elgua_cfg(&self) -> CfgStore {
         CfgStore::new(self.connection.clone())
     }
+
+    pub fn guild_cfg(&self) -> GuildCfgStore {
+        GuildCfgStore::new(self.connection.clone())
+    }
+
+    pub fn loudness(&self) -> LoudnessStore {
+        LoudnessStore::new(self.connection.clone())
+    }
+
+    pub fn stats(&self) -> StatsStore {
+        StatsStore::new(self.connection.clone())
+    }
+
+    pub fn track_queue(&self) -> TrackQueueStore {
+        TrackQueueStore::new(self.connection.clone())
+    }
 }
 
 impl TypeMapKey for Store {
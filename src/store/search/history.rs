@@ -1,118 +1,298 @@
-use futures::AsyncReadExt;
-use serde::{Deserialize, Serialize};
-use tantivy::schema::*;
-use toshi::{AsyncClient, IndexOptions, ToshiClient};
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-use crate::error::ToshiError;
+use tantivy::{
+    collector::{Count, TopDocs},
+    directory::MmapDirectory,
+    query::{BooleanQuery, Occur, Query, QueryParser, TermQuery},
+    schema::*,
+    tokenizer::{LowerCaser, NgramTokenizer, TextAnalyzer},
+    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term,
+};
 
-const HISTORY_INDEX_NAME: &str = "history";
+pub const HISTORY_INDEX: &str = "./cache/index/history";
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 한국어는 띄어쓰기만으로 단어를 나눌 수 없어서 1 ~ 2글자 단위로 나눔
+///
+/// 검색어도 같은 방식으로 나눠서 구문 검색하면 부분 문자열 검색처럼 동작함
+const TOKENIZER: &str = "lang_ko";
+
+/// 파드 메모리 제한이 512Mi라서 색인할 때 최소한만 사용함
+const WRITER_MEMORY_BUDGET: usize = 15_000_000;
+
+#[derive(Debug, Clone)]
 pub struct History {
     pub id: u64,
     pub title: String,
     pub kind: String,
     pub channel: String,
     pub user_id: u64,
-    /// i64 UTC Timestamp
-    pub created_at: String,
+    /// 재생한 서버
+    pub guild_ids: Vec<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    id: Field,
+    title: Field,
+    kind: Field,
+    channel: Field,
+    user_id: Field,
+    guild_id: Field,
 }
 
+#[derive(Clone)]
 pub struct HistoryStore {
-    client: ToshiClient,
+    index: Index,
+    reader: IndexReader,
+    writer: Arc<Mutex<IndexWriter>>,
+    fields: Fields,
 }
 
 impl HistoryStore {
-    pub(crate) async fn init<'c>(url: &str) {
+    pub(crate) fn init(p: impl AsRef<Path>) -> tantivy::Result<Self> {
+        let p = p.as_ref();
+        fs::create_dir_all(p)?;
+
         let mut schema = Schema::builder();
 
-        schema.add_u64_field("id", NumericOptions::default().set_stored());
-        schema.add_text_field(
-            "title",
-            TextOptions::default()
-                .set_indexing_options(
-                    TextFieldIndexing::default()
-                        .set_tokenizer("lang_ko")
-                        .set_index_option(IndexRecordOption::Basic),
-                )
-                .set_stored(),
-        );
-        schema.add_text_field(
-            "kind",
-            TextOptions::default()
-                .set_indexing_options(
-                    TextFieldIndexing::default()
-                        .set_tokenizer("lang_ko")
-                        .set_index_option(IndexRecordOption::Basic),
-                )
-                .set_stored(),
-        );
-        schema.add_text_field(
-            "channel",
-            TextOptions::default()
-                .set_indexing_options(
-                    TextFieldIndexing::default()
-                        .set_tokenizer("lang_ko")
-                        .set_index_option(IndexRecordOption::Basic),
-                )
-                .set_stored(),
-        );
-        schema.add_u64_field(
-            "user_id",
-            NumericOptions::default().set_indexed().set_stored(),
-        );
-        schema.add_date_field(
-            "created_at",
-            NumericOptions::default().set_indexed().set_stored(),
+        let text_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(TOKENIZER)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored();
+
+        let fields = Fields {
+            id: schema.add_u64_field("id", NumericOptions::default().set_indexed().set_stored()),
+            title: schema.add_text_field("title", text_options.clone()),
+            kind: schema.add_text_field("kind", text_options.clone()),
+            channel: schema.add_text_field("channel", text_options),
+            user_id: schema.add_u64_field(
+                "user_id",
+                NumericOptions::default().set_indexed().set_stored(),
+            ),
+            guild_id: schema.add_u64_field("guild_id", NumericOptions::default().set_indexed()),
+        };
+
+        let schema = schema.build();
+
+        // 필드가 바뀌었으면 색인을 지우고 다시 만듦. 비어 있으면 `reindex_if_needed`에서 다시 색인함
+        let index = match Index::open_or_create(MmapDirectory::open(p)?, schema.clone()) {
+            Err(TantivyError::SchemaError(err)) => {
+                log::warn!("recreating history index: {err}");
+
+                fs::remove_dir_all(p)?;
+                fs::create_dir_all(p)?;
+
+                Index::create_in_dir(p, schema)?
+            }
+            x => x?,
+        };
+
+        index.tokenizers().register(
+            TOKENIZER,
+            TextAnalyzer::builder(NgramTokenizer::new(1, 2, false)?)
+                .filter(LowerCaser)
+                .build(),
         );
 
-        let client = ToshiClient::new(url);
+        let writer = index.writer_with_num_threads(1, WRITER_MEMORY_BUDGET)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
 
-        client
-            .create_index(HISTORY_INDEX_NAME, schema.build())
-            .await
-            .unwrap();
+        Ok(Self {
+            index,
+            reader,
+            writer: Arc::new(Mutex::new(writer)),
+            fields,
+        })
     }
 
-    pub(crate) fn new(client: ToshiClient) -> Self {
-        Self { client }
-    }
+    fn document(&self, x: &History) -> TantivyDocument {
+        let Fields {
+            id,
+            title,
+            kind,
+            channel,
+            user_id,
+            guild_id,
+        } = self.fields;
+
+        let mut doc = TantivyDocument::default();
+
+        doc.add_u64(id, x.id);
+        doc.add_text(title, &x.title);
+        doc.add_text(kind, &x.kind);
+        doc.add_text(channel, &x.channel);
+        doc.add_u64(user_id, x.user_id);
+
+        for x in &x.guild_ids {
+            doc.add_u64(guild_id, *x);
+        }
 
-    pub async fn add(&self, doc: &History) -> crate::Result<()> {
-        let index_options = IndexOptions { commit: true };
+        doc
+    }
 
-        let mut resp = self
-            .client
-            .add_document(HISTORY_INDEX_NAME, doc, Some(index_options))
-            .await?;
+    fn commit(&self, mut writer: std::sync::MutexGuard<IndexWriter>) -> tantivy::Result<()> {
+        writer.commit()?;
+        self.reader.reload()
+    }
 
-        let status = resp.status();
+    /// 같은 id의 문서가 있으면 교체함
+    pub fn add(&self, doc: &History) -> tantivy::Result<()> {
+        let writer = self.writer.lock().unwrap();
 
-        log::debug!("status = {}", status);
+        writer.delete_term(Term::from_field_u64(self.fields.id, doc.id));
+        writer.add_document(self.document(doc))?;
 
-        if resp.status().as_u16() == 201 {
-            Ok(())
-        } else {
-            let mut x = Vec::new();
-            resp.body_mut().read_to_end(&mut x).await.unwrap();
+        self.commit(writer)
+    }
 
-            let mut x: crate::error::ToshiError = serde_json::from_slice(&x).unwrap_or_else(|_e| {
-                let a = String::from_utf8(x).unwrap_or_else(|_e| "unknown error".to_string());
-                ToshiError {
-                    status: 0,
-                    message: a,
-                }
-            });
-            x.status = status.as_u16();
+    /// 색인을 비우고 처음부터 다시 만듦
+    pub fn add_all(&self, docs: &[History]) -> tantivy::Result<()> {
+        let writer = self.writer.lock().unwrap();
 
-            log::error!("add: {x}");
+        writer.delete_all_documents()?;
 
-            Err(x.into())
+        for doc in docs {
+            writer.add_document(self.document(doc))?;
         }
+
+        self.commit(writer)
     }
 
-    // 1. title
-    pub async fn _search(&self) -> crate::Result<()> {
-        todo!()
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
     }
+
+    /// guild_id 서버에서 재생한 음악만 찾음.
+    /// `<@유저 ID>` 형식으로 멘션하면 해당 유저가 재생한 음악만 찾음
+    ///
+    /// returns (history ids, total)
+    pub fn search(
+        &self,
+        keyword: &str,
+        guild_id: u64,
+        offset: usize,
+        limit: usize,
+    ) -> tantivy::Result<(Vec<u64>, usize)> {
+        let Fields {
+            id,
+            title,
+            kind,
+            channel,
+            user_id,
+            guild_id: guild_id_field,
+        } = self.fields;
+
+        let (user_ids, words): (Vec<_>, Vec<_>) = keyword
+            .split_whitespace()
+            .partition(|x| parse_user_mention(x).is_some());
+
+        let mut queries: Vec<(Occur, Box<dyn Query>)> = vec![(
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_u64(guild_id_field, guild_id),
+                IndexRecordOption::Basic,
+            )),
+        )];
+
+        if !words.is_empty() {
+            let mut query_parser = QueryParser::for_index(&self.index, vec![title, channel, kind]);
+            query_parser.set_conjunction_by_default();
+
+            let (query, errors) = query_parser.parse_query_lenient(&words.join(" "));
+
+            if !errors.is_empty() {
+                log::debug!("query errors = {errors:?}");
+            }
+
+            queries.push((Occur::Must, query));
+        }
+
+        for x in user_ids.into_iter().filter_map(parse_user_mention) {
+            queries.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_u64(user_id, x),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+
+        let query = BooleanQuery::new(queries);
+
+        let searcher = self.reader.searcher();
+
+        let (top_docs, total) = searcher.search(
+            &query,
+            &(TopDocs::with_limit(limit).and_offset(offset), Count),
+        )?;
+
+        let ids = top_docs
+            .into_iter()
+            .map(|(_score, address)| {
+                let doc: TantivyDocument = searcher.doc(address)?;
+
+                Ok(doc
+                    .get_first(id)
+                    .and_then(|x| x.as_u64())
+                    .unwrap_or_default())
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+
+        Ok((ids, total))
+    }
+}
+
+fn parse_user_mention(x: &str) -> Option<u64> {
+    let x = x.strip_prefix("<@")?.strip_suffix('>')?;
+    let x = x.strip_prefix('!').unwrap_or(x);
+
+    x.parse().ok()
+}
+
+#[test]
+fn test_search() {
+    let p = std::env::temp_dir().join(format!("elgua-test-search-{}", std::process::id()));
+    let store = HistoryStore::init(&p).unwrap();
+
+    store
+        .add_all(&[
+            History {
+                id: 1,
+                title: "MC재앙 - 개구리".to_string(),
+                kind: "youtube".to_string(),
+                channel: "MC재앙".to_string(),
+                user_id: 10,
+                guild_ids: vec![100],
+            },
+            History {
+                id: 2,
+                title: "Never Gonna Give You Up".to_string(),
+                kind: "youtube".to_string(),
+                channel: "Rick Astley".to_string(),
+                user_id: 20,
+                guild_ids: vec![100, 200],
+            },
+        ])
+        .unwrap();
+
+    assert_eq!(store.search("개구", 100, 0, 10).unwrap(), (vec![1], 1));
+    assert_eq!(store.search("rick", 100, 0, 10).unwrap(), (vec![2], 1));
+    assert_eq!(store.search("<@20>", 100, 0, 10).unwrap(), (vec![2], 1));
+    assert_eq!(store.search("재앙 <@20>", 100, 0, 10).unwrap().1, 0);
+    assert_eq!(store.search("youtube", 100, 0, 10).unwrap().1, 2);
+    assert_eq!(store.search("youtube", 200, 0, 10).unwrap(), (vec![2], 1));
+    assert_eq!(store.search("", 200, 0, 10).unwrap(), (vec![2], 1));
+    assert_eq!(store.search("개구", 300, 0, 10).unwrap().1, 0);
+
+    fs::remove_dir_all(p).ok();
 }
@@ -1,3 +1,3 @@
 mod history;
 
-pub use history::{History, HistoryStore};
+pub use history::{History, HistoryStore, HISTORY_INDEX};
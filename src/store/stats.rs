@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, FromRow, PgPool, Row};
+
+use crate::audio::AudioSourceKind;
+
+use super::history::{History, HistoryRow};
+
+/// plays 테이블을 서버별로 집계함
+///
+/// since가 None이면 전체 기간
+pub struct StatsStore {
+    conn: PgPool,
+}
+
+/// 집계한 값과 재생 횟수
+pub struct Ranking<T> {
+    pub value: T,
+    pub count: u64,
+}
+
+impl StatsStore {
+    pub(super) fn new(conn: PgPool) -> Self {
+        Self { conn }
+    }
+
+    /// 서버를 알 수 없는 재생 기록을 이 서버의 기록으로 옮김
+    ///
+    /// 서버별 설정을 도입하기 전에는 하나의 서버에서만 사용했음.
+    /// 옮긴 음악의 재생 기록 메세지와 소리 크기도 이 서버의 것으로 옮김
+    ///
+    /// returns 옮긴 재생 기록 수
+    pub async fn claim_unknown_guild(&self, guild_id: u64) -> sqlx::Result<u64> {
+        let mut conn = self.conn.begin().await?;
+
+        let history_ids: Vec<i64> = sqlx::query_scalar(
+            "UPDATE plays SET guild_id = $1 WHERE guild_id IS NULL RETURNING history_id",
+        )
+        .bind(guild_id as i64)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO guild_history (guild_id, history_id, message_channel_id, message_id, volume)
+            SELECT $1, history.id, guild_cfg.history_channel_id, history.message_id, history.volume
+            FROM history
+            LEFT JOIN guild_cfg ON guild_cfg.guild_id = $1
+            WHERE history.id = ANY($2) AND
+                  (history.message_id IS NOT NULL OR history.volume IS NOT NULL)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(guild_id as i64)
+        .bind(&history_ids)
+        .execute(&mut *conn)
+        .await?;
+
+        conn.commit().await?;
+
+        Ok(history_ids.len() as u64)
+    }
+
+    /// 많이 재생한 음악
+    ///
+    /// returns (rankings, total)
+    pub async fn top_tracks(
+        &self,
+        guild_id: u64,
+        since: Option<DateTime<Utc>>,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<(Vec<Ranking<History>>, usize)> {
+        let rows = sqlx::query(
+            r#"
+            SELECT history.*, count(*) AS play_count, count(*) OVER () AS total
+            FROM plays
+            INNER JOIN history ON history.id = plays.history_id
+            WHERE plays.guild_id = $1 AND
+                  ($2::timestamptz IS NULL OR plays.started_at >= $2)
+            GROUP BY history.id
+            ORDER BY play_count DESC, max(plays.started_at) DESC
+            OFFSET $3
+            LIMIT $4
+            "#,
+        )
+        .bind(guild_id as i64)
+        .bind(since)
+        .bind(offset as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.conn)
+        .await?;
+
+        let total = get_total(&rows)?;
+
+        let rankings = rows
+            .iter()
+            .map(|row| {
+                Ok(Ranking {
+                    value: HistoryRow::from_row(row)?.into(),
+                    count: row.try_get::<i64, _>("play_count")? as u64,
+                })
+            })
+            .collect::<sqlx::Result<_>>()?;
+
+        Ok((rankings, total))
+    }
+
+    /// 많이 재생한 유저
+    ///
+    /// returns (rankings, total)
+    pub async fn top_users(
+        &self,
+        guild_id: u64,
+        since: Option<DateTime<Utc>>,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<(Vec<Ranking<u64>>, usize)> {
+        let (rankings, total) = self
+            .rank::<i64>("plays.user_id", guild_id, since, offset, limit)
+            .await?;
+
+        let rankings = rankings
+            .into_iter()
+            .map(|x| Ranking {
+                value: x.value as u64,
+                count: x.count,
+            })
+            .collect();
+
+        Ok((rankings, total))
+    }
+
+    /// 많이 재생한 음악의 채널
+    ///
+    /// returns (rankings, total)
+    pub async fn top_channels(
+        &self,
+        guild_id: u64,
+        since: Option<DateTime<Utc>>,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<(Vec<Ranking<String>>, usize)> {
+        self.rank("history.channel", guild_id, since, offset, limit)
+            .await
+    }
+
+    /// 음악을 가져온 곳마다 재생한 횟수
+    pub async fn kinds(
+        &self,
+        guild_id: u64,
+        since: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<Vec<Ranking<AudioSourceKind>>> {
+        let (rankings, _total) = self
+            .rank::<String>("history.kind", guild_id, since, 0, usize::MAX)
+            .await?;
+
+        let rankings = rankings
+            .into_iter()
+            .map(|x| Ranking {
+                value: x.value.into(),
+                count: x.count,
+            })
+            .collect();
+
+        Ok(rankings)
+    }
+
+    /// column은 SQL에 그대로 들어가기 때문에 상수만 넘겨야 함
+    async fn rank<T>(
+        &self,
+        column: &'static str,
+        guild_id: u64,
+        since: Option<DateTime<Utc>>,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<(Vec<Ranking<T>>, usize)>
+    where
+        T: for<'r> sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
+    {
+        let sql = format!(
+            r#"
+            SELECT {column} AS value, count(*) AS play_count, count(*) OVER () AS total
+            FROM plays
+            INNER JOIN history ON history.id = plays.history_id
+            WHERE plays.guild_id = $1 AND
+                  ($2::timestamptz IS NULL OR plays.started_at >= $2)
+            GROUP BY {column}
+            ORDER BY play_count DESC, {column}
+            OFFSET $3
+            LIMIT $4
+            "#
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(guild_id as i64)
+            .bind(since)
+            .bind(offset as i64)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.conn)
+            .await?;
+
+        let total = get_total(&rows)?;
+
+        let rankings = rows
+            .iter()
+            .map(|row| {
+                Ok(Ranking {
+                    value: row.try_get("value")?,
+                    count: row.try_get::<i64, _>("play_count")? as u64,
+                })
+            })
+            .collect::<sqlx::Result<_>>()?;
+
+        Ok((rankings, total))
+    }
+}
+
+/// 페이지를 넘어가서 결과가 없으면 0
+fn get_total(rows: &[PgRow]) -> sqlx::Result<usize> {
+    rows.first()
+        .map(|row| row.try_get::<i64, _>("total"))
+        .transpose()
+        .map(|x| x.unwrap_or(0) as usize)
+}
@@ -1,68 +1,252 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::{fs, io, path::PathBuf};
-use tokio::{
-    fs::File,
-    io::{AsyncReadExt, AsyncWriteExt},
-};
+use sqlx::{PgPool, Row};
 
-use super::HistoryKind;
+use crate::audio::AudioSourceKind;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct TrackQueue {
-    title: String,
-    kind: HistoryKind,
-    uid: String,
-    created_at: DateTime<Utc>,
+    pub id: u64,
+    pub guild_id: u64,
+    pub title: String,
+    pub kind: AudioSourceKind,
+    pub uid: String,
+    pub url: String,
+    pub user_id: u64,
+    pub volume: Option<u8>,
+    pub play_count: Option<usize>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TrackQueueRow> for TrackQueue {
+    fn from(x: TrackQueueRow) -> Self {
+        Self {
+            id: x.id as u64,
+            guild_id: x.guild_id as u64,
+            title: x.title,
+            kind: x.kind.into(),
+            uid: x.uid,
+            url: x.url,
+            user_id: x.user_id as u64,
+            volume: x.volume.map(|x| x as u8),
+            play_count: x.play_count.map(|x| x as usize),
+            created_at: x.created_at,
+        }
+    }
 }
 
 /// 대기열의 최대 길이를 설정해야하는데
 /// 기준은 디스코드에서 보여줄 수 있는 만큼 또는 성능에 무리가 가지 않는 만큼
+pub const MAX_LEN: usize = 100;
+
 pub struct TrackQueueStore {
-    path: PathBuf,
+    conn: PgPool,
 }
 
 impl TrackQueueStore {
-    pub fn new(p: impl AsRef<PathBuf>) -> io::Result<Self> {
-        let p = p.as_ref();
-        fs::create_dir_all(p)?;
-
-        Ok(Self { path: p.clone() })
+    pub(super) fn new(conn: PgPool) -> Self {
+        Self { conn }
     }
 
-    async fn read(&self) -> io::Result<Vec<TrackQueue>> {
-        let mut file = File::open(&self.path).await?;
-        let mut buf = Vec::new();
-
-        file.read_to_end(&mut buf).await?;
-
-        Ok(serde_json::from_slice(&buf).unwrap())
+    /// 중복된 노래를 추가하는 경우에는 이전 노래는 지우고 가장 마지막에 다시 추가
+    ///
+    /// returns 추가된 노래의 순서 (1부터 시작)
+    pub async fn push_back(&self, track_queue: &TrackQueue) -> crate::Result<usize> {
+        let mut conn = self.conn.begin().await?;
+
+        // 동시에 추가해도 MAX_LEN을 넘지 않도록 서버마다 하나씩 추가함
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(track_queue.guild_id as i64)
+            .execute(&mut *conn)
+            .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM track_queue
+            WHERE guild_id = $1 AND
+                  kind = $2 AND
+                  uid = $3
+            "#,
+        )
+        .bind(track_queue.guild_id as i64)
+        .bind(track_queue.kind.as_str())
+        .bind(track_queue.uid.as_str())
+        .execute(&mut *conn)
+        .await?;
+
+        let len: i64 = sqlx::query("SELECT COUNT(*) AS len FROM track_queue WHERE guild_id = $1")
+            .bind(track_queue.guild_id as i64)
+            .fetch_one(&mut *conn)
+            .await?
+            .try_get("len")?;
+
+        if len as usize >= MAX_LEN {
+            return Err(crate::error::Error::CustomError(format!(
+                "대기열에는 {MAX_LEN}곡까지만 추가할 수 있어요"
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO track_queue (guild_id, position, title, kind, uid, url, user_id, volume, play_count, created_at)
+            VALUES (
+                $1,
+                COALESCE((SELECT MAX(position) FROM track_queue WHERE guild_id = $1), 0) + 1,
+                $2, $3, $4, $5, $6, $7, $8, $9
+            )
+            "#,
+        )
+        .bind(track_queue.guild_id as i64)
+        .bind(track_queue.title.as_str())
+        .bind(track_queue.kind.as_str())
+        .bind(track_queue.uid.as_str())
+        .bind(track_queue.url.as_str())
+        .bind(track_queue.user_id as i64)
+        .bind(track_queue.volume.map(|x| x as i16))
+        .bind(track_queue.play_count.map(|x| x as i32))
+        .bind(track_queue.created_at)
+        .execute(&mut *conn)
+        .await?;
+
+        conn.commit().await?;
+
+        Ok(len as usize + 1)
     }
 
-    async fn write(&self, queue: Vec<TrackQueue>) -> io::Result<()> {
-        let r = serde_json::to_string(&queue).unwrap();
-
-        let mut file = File::open(&self.path).await?;
-
-        file.write_all(r.as_bytes()).await
+    pub async fn pop_front(&self, guild_id: u64) -> sqlx::Result<Option<TrackQueue>> {
+        let sql = r#"
+            DELETE FROM track_queue
+            WHERE id = (
+                SELECT id FROM track_queue
+                WHERE guild_id = $1
+                ORDER BY position
+                LIMIT 1
+            )
+            RETURNING *
+        "#;
+
+        let track_queue = sqlx::query_as(sql)
+            .bind(guild_id as i64)
+            .fetch_optional(&self.conn)
+            .await?
+            .map(|x: TrackQueueRow| x.into());
+
+        Ok(track_queue)
     }
 
-    /// 중복된 노래를 추가하는 경우에는 이전 노래는 지우고 가장 마지막에 다시 추가
-    pub async fn push_back(&self, track_queue: TrackQueue) -> io::Result<()> {
-        let mut queue = self.read().await?;
-
-        queue.push(track_queue);
+    pub async fn list(&self, guild_id: u64) -> sqlx::Result<Vec<TrackQueue>> {
+        let sql = r#"
+            SELECT * FROM track_queue
+            WHERE guild_id = $1
+            ORDER BY position
+        "#;
+
+        let queue = sqlx::query_as(sql)
+            .bind(guild_id as i64)
+            .fetch_all(&self.conn)
+            .await?
+            .into_iter()
+            .map(|x: TrackQueueRow| x.into())
+            .collect();
+
+        Ok(queue)
+    }
 
-        self.write(queue).await
+    /// index: 1부터 시작. 0이면 None
+    pub async fn remove(&self, guild_id: u64, index: usize) -> sqlx::Result<Option<TrackQueue>> {
+        let Some(offset) = index.checked_sub(1) else {
+            return Ok(None);
+        };
+
+        let sql = r#"
+            DELETE FROM track_queue
+            WHERE id = (
+                SELECT id FROM track_queue
+                WHERE guild_id = $1
+                ORDER BY position
+                OFFSET $2
+                LIMIT 1
+            )
+            RETURNING *
+        "#;
+
+        let track_queue = sqlx::query_as(sql)
+            .bind(guild_id as i64)
+            .bind(offset as i64)
+            .fetch_optional(&self.conn)
+            .await?
+            .map(|x: TrackQueueRow| x.into());
+
+        Ok(track_queue)
     }
 
-    pub async fn clear(&self) -> io::Result<()> {
-        self.write(Vec::new()).await
+    /// from, to: 1부터 시작. to가 대기열보다 뒤면 마지막으로 옮김
+    ///
+    /// returns (옮겨진 노래, 옮겨진 순서)
+    pub async fn move_to(
+        &self,
+        guild_id: u64,
+        from: usize,
+        to: usize,
+    ) -> sqlx::Result<Option<(TrackQueue, usize)>> {
+        let mut conn = self.conn.begin().await?;
+
+        let mut queue: Vec<TrackQueue> = sqlx::query_as(
+            r#"
+            SELECT * FROM track_queue
+            WHERE guild_id = $1
+            ORDER BY position
+            FOR UPDATE
+            "#,
+        )
+        .bind(guild_id as i64)
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|x: TrackQueueRow| x.into())
+        .collect();
+
+        if from == 0 || from > queue.len() || to == 0 {
+            return Ok(None);
+        }
+
+        let track_queue = queue.remove(from - 1);
+        let to = to.min(queue.len() + 1);
+
+        queue.insert(to - 1, track_queue.clone());
+
+        for (position, x) in queue.iter().enumerate() {
+            sqlx::query("UPDATE track_queue SET position = $1 WHERE id = $2")
+                .bind(position as i64 + 1)
+                .bind(x.id as i64)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        conn.commit().await?;
+
+        Ok(Some((track_queue, to)))
     }
 
-    pub async fn remove(&self, uid: impl AsRef<str>) -> io::Result<()> {
-        let uid = uid.as_ref();
+    pub async fn clear(&self, guild_id: u64) -> sqlx::Result<u64> {
+        let r = sqlx::query("DELETE FROM track_queue WHERE guild_id = $1")
+            .bind(guild_id as i64)
+            .execute(&self.conn)
+            .await?;
 
-        todo!()
+        Ok(r.rows_affected())
     }
 }
+
+#[derive(sqlx::FromRow)]
+struct TrackQueueRow {
+    id: i64,
+    guild_id: i64,
+    title: String,
+    kind: String,
+    uid: String,
+    url: String,
+    user_id: i64,
+    volume: Option<i16>,
+    play_count: Option<i32>,
+    created_at: DateTime<Utc>,
+}
@@ -1,10 +1,46 @@
-use serenity::prelude::TypeMapKey;
+use std::{collections::HashMap, time::Duration};
+
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::TypeMapKey,
+};
 use songbird::tracks::TrackHandle;
 
-use crate::audio::AudioMetadata;
+use crate::audio::{filter::Filter, AudioMetadata};
 
 pub struct Track(pub AudioMetadata, pub TrackHandle);
 
+/// 서버별로 재생 중인 음악
 impl TypeMapKey for Track {
-    type Value = Track;
+    type Value = HashMap<GuildId, Track>;
+}
+
+/// 서버별로 재생 중인 음악을 재생한 사람
+///
+/// 재생한 사람은 권한이 없어도 그 음악을 조작할 수 있음
+pub struct Requester;
+
+impl TypeMapKey for Requester {
+    type Value = HashMap<GuildId, UserId>;
+}
+
+/// 서버에 적용한 필터
+///
+/// ffmpeg를 중간부터 다시 실행하면 songbird의 재생 시간은 0부터 시작하기 때문에
+/// 원래 음악에서 시작한 위치를 같이 기록함
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActiveFilter {
+    pub filter: Filter,
+    pub offset: Duration,
+}
+
+impl ActiveFilter {
+    /// songbird의 재생 시간을 원래 음악의 재생 시간으로 바꿈
+    pub fn source_position(&self, position: Duration) -> Duration {
+        self.offset + position.mul_f64(self.filter.speed())
+    }
+}
+
+impl TypeMapKey for ActiveFilter {
+    type Value = HashMap<GuildId, ActiveFilter>;
 }
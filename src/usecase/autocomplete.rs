@@ -0,0 +1,212 @@
+//! `/play`의 music을 입력하는 동안 이 서버의 재생 기록과 유튜브 검색 결과를 추천함
+//!
+//! 유튜브 API로 검색하면 할당량을 많이 사용해서 yt-dlp로 검색함.
+//! 글자를 입력할 때마다 요청이 오기 때문에 잠깐 기다렸다가 마지막 요청만 유튜브에서 검색하고
+//! 검색 결과는 입력한 내용마다 저장해 둠
+
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::{Context, TypeMapKey},
+};
+
+use crate::{
+    audio::{
+        provider::{self, YouTube},
+        AudioMetadata, AudioSource,
+    },
+    store::Store,
+};
+
+/// 디스코드가 보여주는 최대 갯수
+const LIMIT: usize = 25;
+
+const HISTORY_LIMIT: usize = 10;
+
+/// 많이 가져올수록 yt-dlp가 느려져서 응답 시간(3초)을 넘지 않도록 조금만 가져옴
+const SEARCH_LIMIT: usize = 5;
+
+/// 추천의 이름과 값은 100자까지 보낼 수 있음
+const MAX_LEN: usize = 100;
+
+/// 이보다 짧으면 유튜브에서 검색하지 않음
+const MIN_SEARCH_LEN: usize = 2;
+
+/// 이 시간 안에 다음 글자를 입력하면 유튜브에서 검색하지 않음
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const CACHE_MAX_LEN: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub name: String,
+    /// 선택하면 music에 들어가는 음악의 주소
+    pub url: String,
+}
+
+impl From<&AudioMetadata> for Suggestion {
+    fn from(x: &AudioMetadata) -> Self {
+        let name = if x.uploaded_by.is_empty() {
+            x.title.clone()
+        } else {
+            format!("{} - {}", x.title, x.uploaded_by)
+        };
+
+        Self {
+            name: truncate(&name, MAX_LEN),
+            url: x.url.clone(),
+        }
+    }
+}
+
+/// 입력한 내용별 유튜브 검색 결과
+pub struct SearchCache;
+
+impl TypeMapKey for SearchCache {
+    type Value = HashMap<String, (Instant, Vec<Suggestion>)>;
+}
+
+/// 유저별로 마지막으로 받은 요청의 id
+pub struct LatestRequest;
+
+impl TypeMapKey for LatestRequest {
+    type Value = HashMap<UserId, u64>;
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+pub async fn autocomplete(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    keyword: &str,
+) -> crate::Result<Vec<Suggestion>> {
+    let keyword = keyword.trim();
+
+    // 주소를 입력하면 그대로 재생함
+    if provider::find(keyword).is_some() {
+        return Ok(Vec::new());
+    }
+
+    let histories = {
+        let x = ctx.data.read().await;
+        let store = x.get::<Store>().unwrap();
+
+        store
+            .history()
+            .find_by_title(keyword, guild_id.get(), HISTORY_LIMIT)
+            .await?
+    };
+
+    let mut suggestions = histories
+        .into_iter()
+        .map(|x| Suggestion::from(AudioSource::from_history(x).metadata()))
+        .collect::<Vec<_>>();
+
+    if keyword.chars().count() >= MIN_SEARCH_LEN {
+        let searched = match search(ctx, user_id, keyword).await {
+            Ok(searched) => searched,
+            Err(err) => {
+                log::warn!("failed to search suggestions: {err}");
+                Vec::new()
+            }
+        };
+
+        for x in searched {
+            if !suggestions.iter().any(|suggestion| suggestion.url == x.url) {
+                suggestions.push(x);
+            }
+        }
+    }
+
+    // 첨부 파일처럼 주소가 긴 음악은 선택해도 재생할 수 없어서 뺌
+    suggestions.retain(|x| x.url.chars().count() <= MAX_LEN);
+    suggestions.truncate(LIMIT);
+
+    Ok(suggestions)
+}
+
+/// 다음 요청이 오면 빈 결과
+async fn search(ctx: &Context, user_id: UserId, keyword: &str) -> crate::Result<Vec<Suggestion>> {
+    let key = keyword.to_lowercase();
+
+    let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+
+    {
+        let mut x = ctx.data.write().await;
+
+        if let Some((cached_at, cached)) = x.get::<SearchCache>().and_then(|x| x.get(&key)) {
+            if cached_at.elapsed() < CACHE_TTL {
+                return Ok(cached.clone());
+            }
+        }
+
+        x.entry::<LatestRequest>()
+            .or_default()
+            .insert(user_id, request_id);
+    }
+
+    tokio::time::sleep(DEBOUNCE).await;
+
+    {
+        let x = ctx.data.read().await;
+
+        let is_latest = x
+            .get::<LatestRequest>()
+            .and_then(|x| x.get(&user_id))
+            .is_some_and(|x| *x == request_id);
+
+        if !is_latest {
+            return Ok(Vec::new());
+        }
+    }
+
+    let searched = YouTube::search(keyword, SEARCH_LIMIT)
+        .await?
+        .iter()
+        .map(Suggestion::from)
+        .collect::<Vec<_>>();
+
+    let mut x = ctx.data.write().await;
+    let cache = x.entry::<SearchCache>().or_default();
+
+    cache.retain(|_, (cached_at, _)| cached_at.elapsed() < CACHE_TTL);
+
+    if cache.len() >= CACHE_MAX_LEN {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, (cached_at, _))| *cached_at)
+            .map(|(key, _)| key.clone());
+
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+
+    cache.insert(key, (Instant::now(), searched.clone()));
+
+    Ok(searched)
+}
+
+fn truncate(x: &str, len: usize) -> String {
+    if x.chars().count() <= len {
+        return x.to_string();
+    }
+
+    let mut r = x.chars().take(len - 1).collect::<String>();
+    r.push('…');
+    r
+}
+
+#[test]
+fn test_truncate() {
+    assert_eq!(truncate("abc", 3), "abc");
+    assert_eq!(truncate("abcd", 3), "ab…");
+    assert_eq!(truncate("가나다라", 3).chars().count(), 3);
+}
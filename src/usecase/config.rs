@@ -0,0 +1,68 @@
+use serenity::{model::id::GuildId, prelude::Context};
+
+use crate::store::{CfgKey, Store};
+
+fn describe(key: CfgKey, value: Option<&str>) -> String {
+    let value = match value {
+        Some(value) => value.to_string(),
+        None => format!("{} (기본값)", key.default_value().unwrap_or("없음")),
+    };
+
+    format!("`{}` = {value}\n-# {}", key.as_str(), key.description())
+}
+
+pub async fn get(ctx: &Context, guild_id: GuildId, key: CfgKey) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let value = store.elgua_cfg().get(key, Some(guild_id)).await?;
+
+    Ok(describe(key, value.as_deref()))
+}
+
+pub async fn set(
+    ctx: &Context,
+    guild_id: GuildId,
+    key: CfgKey,
+    value: &str,
+) -> crate::Result<String> {
+    let value = match key.validate(value) {
+        Ok(value) => value,
+        Err(err) => return Ok(format!("`{}`: {err}", key.as_str())),
+    };
+
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    store
+        .elgua_cfg()
+        .add_or_update(key, Some(guild_id), &value)
+        .await?;
+
+    Ok(describe(key, Some(&value)))
+}
+
+pub async fn reset(ctx: &Context, guild_id: GuildId, key: CfgKey) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    store.elgua_cfg().remove(key, Some(guild_id)).await?;
+
+    Ok(describe(key, None))
+}
+
+/// 서버마다 정할 수 있는 설정만 보여줌
+pub async fn list(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let mut r = Vec::new();
+
+    for key in CfgKey::guild_keys() {
+        let value = store.elgua_cfg().get(key, Some(guild_id)).await?;
+
+        r.push(describe(key, value.as_deref()));
+    }
+
+    Ok(r.join("\n"))
+}
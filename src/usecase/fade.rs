@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use songbird::tracks::{PlayMode, TrackHandle};
+
+/// 소리 크기를 바꾸는 간격
+const STEP: Duration = Duration::from_millis(50);
+
+/// duration 동안 소리 크기를 from에서 to로 조금씩 바꿈
+///
+/// 도중에 `/volume`으로 소리 크기가 바뀌었거나 음악이 끝났으면 멈추고 false
+async fn ramp(track: &TrackHandle, from: f32, to: f32, duration: Duration) -> bool {
+    let steps = (duration.as_millis() / STEP.as_millis()).max(1) as u32;
+
+    let mut interval = tokio::time::interval(STEP);
+    let mut current = from;
+
+    for i in 1..=steps {
+        interval.tick().await;
+
+        match track.get_info().await {
+            Ok(info) if info.volume == current => {}
+            _ => return false,
+        }
+
+        current = from + (to - from) * i as f32 / steps as f32;
+
+        if track.set_volume(current).is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 소리 크기 0에서 재생을 시작한 음악의 소리 크기를 volume까지 키움
+pub fn fade_in(track: TrackHandle, volume: f32, duration: Duration) {
+    tokio::spawn(async move {
+        ramp(&track, 0.0, volume, duration).await;
+    });
+}
+
+/// 음악의 소리 크기를 0까지 줄이고 중지함
+pub fn fade_out(track: TrackHandle, duration: Duration) {
+    tokio::spawn(async move {
+        if let Ok(info) = track.get_info().await {
+            // 일시정지한 음악은 소리가 나지 않으니 바로 중지함
+            if info.playing == PlayMode::Play {
+                ramp(&track, info.volume, 0.0, duration).await;
+            }
+        }
+
+        track.stop().ok();
+    });
+}
@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use serenity::{
+    model::id::{ChannelId, GuildId},
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use songbird::{events::EventContext, tracks::PlayMode};
+use uuid::Uuid;
+
+use crate::{
+    audio::{filter::Filter, progressive, AudioMetadata, AudioSource},
+    event::{Event, EventSender},
+    track::{ActiveFilter, Track},
+};
+
+/// 반복해서 처음부터 다시 재생하면 원래 음악에서 시작한 위치를 0으로 되돌림
+pub struct ResetFilterOffset {
+    ctx: Context,
+    guild_id: GuildId,
+    uuid: Uuid,
+}
+
+impl ResetFilterOffset {
+    pub fn new(ctx: Context, guild_id: GuildId, uuid: Uuid) -> Self {
+        Self {
+            ctx,
+            guild_id,
+            uuid,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl songbird::EventHandler for ResetFilterOffset {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<songbird::Event> {
+        let ctx = self.ctx.clone();
+        let guild_id = self.guild_id;
+        let uuid = self.uuid;
+
+        tokio::spawn(async move {
+            let mut x = ctx.data.write().await;
+
+            let is_current = x
+                .get::<Track>()
+                .and_then(|x| x.get(&guild_id))
+                .map(|Track(_, track)| track.uuid() == uuid)
+                .unwrap_or(false);
+
+            if !is_current {
+                return;
+            }
+
+            if let Some(active_filter) = x
+                .get_mut::<ActiveFilter>()
+                .and_then(|x| x.get_mut(&guild_id))
+            {
+                active_filter.offset = Duration::ZERO;
+            }
+        });
+
+        None
+    }
+}
+
+pub async fn filter(ctx: &Context, guild_id: GuildId, filter: Filter) -> crate::Result<String> {
+    let (active_filter, position) = {
+        let x = ctx.data.read().await;
+
+        let active_filter = x
+            .get::<ActiveFilter>()
+            .and_then(|x| x.get(&guild_id))
+            .copied()
+            .unwrap_or_default();
+
+        let position = match x.get::<Track>().and_then(|x| x.get(&guild_id)) {
+            Some(Track(_, track)) => track
+                .get_info()
+                .await
+                .ok()
+                .filter(|x| matches!(x.playing, PlayMode::Play | PlayMode::Pause))
+                .map(|x| active_filter.source_position(x.position)),
+            None => None,
+        };
+
+        (active_filter, position)
+    };
+
+    if active_filter.filter == filter {
+        return Ok(match filter {
+            Filter::Off => "적용한 필터가 없어요".to_string(),
+            _ => format!("이미 {} 필터를 적용하고 있어요", filter.name()),
+        });
+    }
+
+    let restarted = match position {
+        Some(position) => restart(ctx, guild_id, filter, position).await?,
+        None => None,
+    };
+
+    let Some(audio_metadata) = restarted else {
+        let mut x = ctx.data.write().await;
+
+        x.entry::<ActiveFilter>().or_default().insert(
+            guild_id,
+            ActiveFilter {
+                filter,
+                offset: Duration::ZERO,
+            },
+        );
+
+        return Ok(match filter {
+            Filter::Off => "필터를 껐어요".to_string(),
+            _ => format!("다음 음악부터 {} 필터를 적용해요", filter.name()),
+        });
+    };
+
+    let mut r = MessageBuilder::new()
+        .push_named_link(&audio_metadata.title, &audio_metadata.url)
+        .to_owned();
+
+    match filter {
+        Filter::Off => r.push("\n필터를 껐어요"),
+        _ => r.push("\n필터: ").push(filter.name()),
+    };
+
+    Ok(r.build())
+}
+
+/// 재생 중인 음악을 원래 음악의 position부터 filter를 적용해서 다시 재생함
+///
+/// 소리 크기, 남은 재생 횟수, 일시 정지한 상태는 그대로 이어감
+///
+/// 재생 중인 음악이 없으면 None
+pub(super) async fn restart(
+    ctx: &Context,
+    guild_id: GuildId,
+    filter: Filter,
+    position: Duration,
+) -> crate::Result<Option<AudioMetadata>> {
+    // 다 받을 때까지 다른 명령어를 막지 않도록 잠그기 전에 기다림
+    let playing = {
+        let x = ctx.data.read().await;
+
+        x.get::<Track>()
+            .and_then(|x| x.get(&guild_id))
+            .map(|Track(audio_metadata, _)| (audio_metadata.kind(), audio_metadata.id.clone()))
+    };
+
+    if let Some((kind, id)) = playing {
+        progressive::wait(kind, &id).await;
+    }
+
+    let Some(handler) = songbird::get(ctx).await.unwrap().get(guild_id) else {
+        return Ok(None);
+    };
+    let mut handler = handler.lock().await;
+
+    let Some(voice_channel_id) = handler.current_channel() else {
+        return Ok(None);
+    };
+    let voice_channel_id = ChannelId::new(voice_channel_id.0.get());
+
+    let mut x = ctx.data.write().await;
+
+    let Some(Track(audio_metadata, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) else {
+        return Ok(None);
+    };
+
+    let Ok(info) = track.get_info().await else {
+        return Ok(None);
+    };
+
+    if !matches!(info.playing, PlayMode::Play | PlayMode::Pause) {
+        return Ok(None);
+    }
+
+    let audio_metadata = audio_metadata.clone();
+    let prev_uuid = track.uuid();
+
+    let source = AudioSource::from(audio_metadata.clone())
+        .get_filtered_source(filter, position)
+        .await?;
+
+    let uuid = Uuid::new_v4();
+
+    // 이전 음악이 끝난 기록보다 먼저 처리되도록 멈추기 전에 보냄
+    let event_tx = x.get::<EventSender>().cloned().unwrap();
+
+    if let Err(err) = event_tx
+        .send((ctx.clone(), Event::Restart(prev_uuid, uuid)))
+        .await
+    {
+        log::error!("closed event channel: {err}");
+    }
+
+    let mut next = songbird::tracks::Track::new_with_uuid(source, uuid)
+        .volume(info.volume)
+        .loops(info.loops);
+
+    if info.playing == PlayMode::Pause {
+        next = next.pause();
+    }
+
+    // 이전 음악의 AutoAdvance가 다음 음악을 재생하지 않도록 바꾼 다음에 ctx.data를 놓음
+    let track = handler.play_only(next);
+
+    super::play::add_track_events(ctx, guild_id, voice_channel_id, &track)?;
+
+    // 필터를 끄면 원래 파일에서 재생 위치를 옮길 수 있음
+    let offset = match filter {
+        Filter::Off => {
+            if !position.is_zero() {
+                let _ = track.seek(position);
+            }
+
+            Duration::ZERO
+        }
+        _ => position,
+    };
+
+    x.entry::<Track>()
+        .or_default()
+        .insert(guild_id, Track(audio_metadata.clone(), track));
+
+    x.entry::<ActiveFilter>()
+        .or_default()
+        .insert(guild_id, ActiveFilter { filter, offset });
+
+    Ok(Some(audio_metadata))
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serenity::{
+    model::{
+        id::{GuildId, UserId},
+        voice::VoiceState,
+    },
+    prelude::{Context, TypeMap, TypeMapKey},
+};
+use songbird::tracks::PlayMode;
+
+use crate::{
+    cfg::Cfg,
+    track::{Requester, Track},
+};
+
+/// 서버별로 실행 중인 나가기 타이머의 id
+///
+/// 타이머를 새로 시작하거나 취소하면 id가 바뀌어서 이전 타이머는 나가지 않음
+pub struct IdleTimer;
+
+impl TypeMapKey for IdleTimer {
+    type Value = HashMap<GuildId, u64>;
+}
+
+/// 음성 채널에 아무도 없어서 자동으로 일시정지한 서버
+pub struct AutoPaused;
+
+impl TypeMapKey for AutoPaused {
+    type Value = HashSet<GuildId>;
+}
+
+static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 설정된 시간 동안 취소되지 않으면 음성 채널에서 나감
+///
+/// 실행 중인 타이머가 있으면 처음부터 다시 시작함
+pub async fn start_idle_timer(ctx: &Context, guild_id: GuildId) {
+    let timer_id = NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed);
+
+    let idle_timeout = {
+        let mut x = ctx.data.write().await;
+
+        x.entry::<IdleTimer>()
+            .or_default()
+            .insert(guild_id, timer_id);
+
+        Duration::from_secs(x.get::<Cfg>().unwrap().idle_timeout_secs)
+    };
+
+    log::debug!("start idle timer: guild_id = {guild_id}");
+
+    let ctx = ctx.clone();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(idle_timeout).await;
+
+        {
+            let mut x = ctx.data.write().await;
+            let timers = x.entry::<IdleTimer>().or_default();
+
+            if timers.get(&guild_id) != Some(&timer_id) {
+                return;
+            }
+
+            timers.remove(&guild_id);
+            x.entry::<AutoPaused>().or_default().remove(&guild_id);
+        }
+
+        log::info!("leave idle voice channel: guild_id = {guild_id}");
+
+        if let Err(err) = super::leave(&ctx, guild_id).await {
+            log::error!("{err}");
+        }
+    });
+}
+
+pub async fn cancel_idle_timer(ctx: &Context, guild_id: GuildId) {
+    let mut x = ctx.data.write().await;
+
+    cancel(&mut x, guild_id);
+}
+
+/// 이미 ctx.data를 잠근 곳에서 호출함
+pub fn cancel(x: &mut TypeMap, guild_id: GuildId) {
+    if let Some(timers) = x.get_mut::<IdleTimer>() {
+        if timers.remove(&guild_id).is_some() {
+            log::debug!("cancel idle timer: guild_id = {guild_id}");
+        }
+    }
+}
+
+/// 봇이 들어가 있는 음성 채널에 봇 말고 아무도 없으면 true
+///
+/// 서버에 처음 연결할 때 받은 음성 상태에는 member가 없어서 캐시된 멤버로 확인함
+///
+/// 음성 채널에 들어가 있지 않으면 None
+fn is_alone(ctx: &Context, guild_id: GuildId) -> Option<bool> {
+    listeners(ctx, guild_id).map(|x| x.is_empty())
+}
+
+/// 봇이 들어가 있는 음성 채널에서 듣고 있는 사람. 다른 봇은 빼고 셈
+///
+/// 봇이 음성 채널에 들어가 있지 않으면 None
+pub(super) fn listeners(ctx: &Context, guild_id: GuildId) -> Option<HashSet<UserId>> {
+    let current_user_id = ctx.cache.current_user().id;
+    let guild = ctx.cache.guild(guild_id)?;
+
+    let channel_id = guild.voice_states.get(&current_user_id)?.channel_id?;
+
+    let listeners = guild
+        .voice_states
+        .values()
+        .filter(|x| {
+            x.channel_id == Some(channel_id)
+                && x.user_id != current_user_id
+                && !x
+                    .member
+                    .as_ref()
+                    .or_else(|| guild.members.get(&x.user_id))
+                    .map(|x| x.user.bot)
+                    .unwrap_or(false)
+        })
+        .map(|x| x.user_id)
+        .collect();
+
+    Some(listeners)
+}
+
+/// 음성 채널에 아무도 없으면 일시정지하고 나가기 타이머를 시작함
+///
+/// 다시 누군가 들어오면 자동으로 일시정지했던 음악을 다시 재생함
+pub async fn voice_state_update(
+    ctx: &Context,
+    old: Option<&VoiceState>,
+    new: &VoiceState,
+) -> crate::Result<()> {
+    let Some(guild_id) = new.guild_id.or_else(|| old.and_then(|x| x.guild_id)) else {
+        return Ok(());
+    };
+
+    // 봇이 음성 채널에서 나갔거나 내보내짐
+    if new.user_id == ctx.cache.current_user().id && new.channel_id.is_none() {
+        let mut x = ctx.data.write().await;
+
+        cancel(&mut x, guild_id);
+        x.entry::<AutoPaused>().or_default().remove(&guild_id);
+
+        if let Some(tracks) = x.get_mut::<Track>() {
+            tracks.remove(&guild_id);
+        }
+
+        if let Some(requesters) = x.get_mut::<Requester>() {
+            requesters.remove(&guild_id);
+        }
+
+        return Ok(());
+    }
+
+    let Some(is_alone) = is_alone(ctx, guild_id) else {
+        return Ok(());
+    };
+
+    let track = {
+        let x = ctx.data.read().await;
+
+        x.get::<Track>()
+            .and_then(|x| x.get(&guild_id))
+            .map(|Track(_, track)| track.clone())
+    };
+
+    let play_state = match &track {
+        Some(track) => track
+            .get_info()
+            .await
+            .map(|x| x.playing)
+            .unwrap_or(PlayMode::End),
+        None => PlayMode::End,
+    };
+
+    if is_alone {
+        if let (Some(track), PlayMode::Play) = (&track, &play_state) {
+            track.pause()?;
+
+            {
+                let mut x = ctx.data.write().await;
+                x.entry::<AutoPaused>().or_default().insert(guild_id);
+            }
+
+            log::info!("auto paused: guild_id = {guild_id}");
+
+            start_idle_timer(ctx, guild_id).await;
+        } else {
+            let is_idle = {
+                let x = ctx.data.read().await;
+
+                x.get::<IdleTimer>()
+                    .map(|x| x.contains_key(&guild_id))
+                    .unwrap_or(false)
+            };
+
+            // 이미 실행 중인 타이머는 다시 시작하지 않음
+            if !is_idle {
+                start_idle_timer(ctx, guild_id).await;
+            }
+        }
+    } else {
+        let was_auto_paused = {
+            let mut x = ctx.data.write().await;
+            x.entry::<AutoPaused>().or_default().remove(&guild_id)
+        };
+
+        if let (true, Some(track), PlayMode::Pause) = (was_auto_paused, &track, &play_state) {
+            track.play()?;
+
+            log::info!("auto resumed: guild_id = {guild_id}");
+        }
+
+        // 재생 중인 음악이 없으면 나가기 타이머는 그대로 둠
+        if was_auto_paused || matches!(play_state, PlayMode::Play) {
+            cancel_idle_timer(ctx, guild_id).await;
+        }
+    }
+
+    Ok(())
+}
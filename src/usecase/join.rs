@@ -0,0 +1,20 @@
+use serenity::{
+    model::id::{ChannelId, GuildId},
+    prelude::Context,
+    utils::MessageBuilder,
+};
+
+use super::play::get_voice_handler;
+
+pub async fn join(
+    ctx: &Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+) -> crate::Result<String> {
+    get_voice_handler(ctx, guild_id, voice_channel_id).await?;
+
+    Ok(MessageBuilder::new()
+        .channel(voice_channel_id)
+        .push(" 채널에 들어왔어요")
+        .build())
+}
@@ -0,0 +1,34 @@
+use serenity::{model::id::GuildId, prelude::Context};
+
+use crate::track::{ActiveFilter, Requester, Track};
+
+pub async fn leave(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    let manager = songbird::get(ctx).await.unwrap().clone();
+
+    if manager.get(guild_id).is_none() {
+        return Ok("음성 채널에 들어가 있지 않아요".to_string());
+    }
+
+    // 나가면서 끝나는 음악 때문에 대기열의 다음 음악이 재생되지 않도록 먼저 지움
+    {
+        let mut x = ctx.data.write().await;
+
+        if let Some(tracks) = x.get_mut::<Track>() {
+            tracks.remove(&guild_id);
+        }
+
+        if let Some(requesters) = x.get_mut::<Requester>() {
+            requesters.remove(&guild_id);
+        }
+
+        if let Some(filters) = x.get_mut::<ActiveFilter>() {
+            filters.remove(&guild_id);
+        }
+
+        super::idle::cancel(&mut x, guild_id);
+    }
+
+    manager.remove(guild_id).await?;
+
+    Ok("음성 채널에서 나갔어요".to_string())
+}
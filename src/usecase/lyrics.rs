@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use serenity::{
+    model::id::GuildId,
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use songbird::tracks::PlayMode;
+
+use crate::{
+    audio::{
+        lyrics::{self, Line},
+        AudioMetadata,
+    },
+    track::{ActiveFilter, Track},
+};
+
+/// 재생 중인 줄 앞에 보여줄 줄 수
+const BEFORE: usize = 2;
+/// 재생 중인 줄 뒤에 보여줄 줄 수
+const AFTER: usize = 3;
+
+/// 재생 중인 음악과 원래 음악의 재생 시간
+async fn now_playing(ctx: &Context, guild_id: GuildId) -> Option<(AudioMetadata, Duration)> {
+    let x = ctx.data.read().await;
+
+    let Track(audio_metadata, track) = x.get::<Track>()?.get(&guild_id)?;
+
+    let info = track.get_info().await.ok()?;
+
+    if !matches!(info.playing, PlayMode::Play | PlayMode::Pause) {
+        return None;
+    }
+
+    let position = x
+        .get::<ActiveFilter>()
+        .and_then(|x| x.get(&guild_id))
+        .copied()
+        .unwrap_or_default()
+        .source_position(info.position);
+
+    Some((audio_metadata.clone(), position))
+}
+
+/// 재생 중인 음악의 가사를 재생 시간에 맞춰서 보여줌
+///
+/// 가사가 있으면 이어서 바꿀 수 있도록 음악의 정보를 같이 반환함
+pub async fn lyrics(
+    ctx: &Context,
+    guild_id: GuildId,
+) -> crate::Result<(String, Option<AudioMetadata>)> {
+    let Some((audio_metadata, position)) = now_playing(ctx, guild_id).await else {
+        return Ok(("재생 중인 음악이 없어요".to_string(), None));
+    };
+
+    let kind = audio_metadata.kind();
+
+    let Some(lines) = lyrics::load(kind, &audio_metadata.id).await else {
+        let r = if kind.provider().has_subtitles() {
+            "가사를 찾을 수 없어요"
+        } else {
+            "유튜브 음악만 가사를 보여줄 수 있어요"
+        };
+
+        return Ok((r.to_string(), None));
+    };
+
+    let r = render(&audio_metadata, &lines, position);
+
+    Ok((r, Some(audio_metadata)))
+}
+
+/// 같은 음악을 재생 중이면 바뀐 재생 시간에 맞춰서 가사를 보여줌
+///
+/// 음악이 끝났거나 바뀌었으면 None
+pub async fn follow(
+    ctx: &Context,
+    guild_id: GuildId,
+    audio_metadata: &AudioMetadata,
+) -> crate::Result<Option<String>> {
+    let Some((now, position)) = now_playing(ctx, guild_id).await else {
+        return Ok(None);
+    };
+
+    if now.kind() != audio_metadata.kind() || now.id != audio_metadata.id {
+        return Ok(None);
+    }
+
+    let lines = lyrics::load(now.kind(), &now.id).await;
+
+    Ok(lines.map(|lines| render(&now, &lines, position)))
+}
+
+fn render(audio_metadata: &AudioMetadata, lines: &[Line], position: Duration) -> String {
+    let current = lyrics::current(lines, position);
+
+    let center = current.unwrap_or(0);
+    let from = center.saturating_sub(BEFORE);
+    let to = (center + AFTER + 1).min(lines.len());
+
+    let mut r = MessageBuilder::new()
+        .push_named_link(&audio_metadata.title, &audio_metadata.url)
+        .push("\n")
+        .to_owned();
+
+    for (i, line) in lines.iter().enumerate().take(to).skip(from) {
+        r.push("\n");
+
+        if Some(i) == current {
+            r.push_bold_safe(line.text.as_str());
+        } else {
+            r.push_safe(line.text.as_str());
+        }
+    }
+
+    r.build()
+}
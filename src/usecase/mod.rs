@@ -1,9 +1,38 @@
+pub mod autocomplete;
+pub mod config;
+pub mod fade;
+pub mod filter;
+pub mod idle;
+pub mod join;
+pub mod leave;
+pub mod lyrics;
+pub mod pause;
 pub mod play;
+pub mod queue;
+pub mod resume;
+pub mod search;
+pub mod seek;
+pub mod setup;
+pub mod stats;
 pub mod stop;
 pub mod track;
 pub mod volume;
+pub mod vote;
+pub mod ytdlp;
 
+pub use autocomplete::autocomplete;
+pub use filter::filter;
+pub use join::join;
+pub use leave::leave;
+pub use lyrics::lyrics;
+pub use pause::pause;
 pub use play::play;
+pub use queue::skip;
+pub use resume::resume;
+pub use search::search;
+pub use seek::seek;
+pub use setup::setup;
+pub use stats::stats;
 pub use stop::stop;
 pub use track::track;
 pub use volume::volume;
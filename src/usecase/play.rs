@@ -1,24 +1,34 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashSet, io, sync::Arc, time::Duration};
 
+use chrono::Utc;
 use serenity::{
-    model::id::{ChannelId, GuildId, MessageId},
-    prelude::{Context, Mutex},
+    model::id::{ChannelId, GuildId, MessageId, UserId},
+    prelude::{Context, Mutex, TypeMap},
 };
 use songbird::{
     error::{ControlError, JoinError},
-    tracks::PlayMode,
-    Call,
+    events::EventContext,
+    tracks::{PlayMode, TrackHandle},
+    Call, TrackEvent,
 };
 
 use crate::{
-    audio::AudioSource,
-    audio::{scdl, ytdl, AudioMetadata, AudioSourceError},
+    audio::{cache::AudioCache, progressive, provider::Credentials, AudioSource},
+    audio::{
+        loudness::{self, Volume},
+        AudioMetadata, AudioSourceKind,
+    },
     cfg::Cfg,
-    store::{CfgKey, HistoryKind, Store},
-    track::Track,
+    event::{Event, EventSender},
+    store::{CfgKey, Store},
+    track::{ActiveFilter, Requester, Track},
 };
 
-async fn get_voice_handler(
+use uuid::Uuid;
+
+use super::{fade, filter::ResetFilterOffset, queue::AutoAdvance};
+
+pub(super) async fn get_voice_handler(
     ctx: &Context,
     guild_id: GuildId,
     channel_id: ChannelId,
@@ -28,15 +38,15 @@ async fn get_voice_handler(
 }
 
 pub struct Parameter {
-    url: String,
-    kind: PlayableKind,
-    volume: Option<f32>,
-    play_count: Option<usize>,
+    pub(super) url: String,
+    pub(super) kind: AudioSourceKind,
+    pub(super) volume: Option<f32>,
+    pub(super) play_count: Option<usize>,
 }
 
 impl Parameter {
     pub fn new(
-        kind: PlayableKind,
+        kind: AudioSourceKind,
         url: String,
         volume: Option<f32>,
         play_count: Option<usize>,
@@ -50,120 +60,127 @@ impl Parameter {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum PlayableKind {
-    YouTube,
-    SoundCloud,
+/// 사운드클라우드 Client ID는 /sc 명령어로 업데이트한 값을 우선으로 사용함
+pub(super) async fn get_credentials(x: &TypeMap) -> crate::Result<Credentials> {
+    let cfg = x.get::<Cfg>().unwrap();
+    let store = x.get::<Store>().unwrap();
+
+    let sc_client_id = store
+        .elgua_cfg()
+        .get(CfgKey::SoundCloudApiKey, None)
+        .await?;
+
+    Ok(Credentials {
+        youtube_api_key: cfg.youtube_api_key.clone(),
+        soundcloud_client_id: sc_client_id.unwrap_or_else(|| cfg.soundcloud_client_id.clone()),
+    })
 }
 
-impl From<PlayableKind> for HistoryKind {
-    fn from(x: PlayableKind) -> Self {
-        match x {
-            PlayableKind::YouTube => Self::YouTube,
-            PlayableKind::SoundCloud => Self::SoundCloud,
-        }
-    }
+/// 재생 기록 버튼으로 다시 재생할 주소
+pub async fn find_url(
+    ctx: &Context,
+    kind: AudioSourceKind,
+    uid: &str,
+) -> crate::Result<Option<String>> {
+    let history = {
+        let x = ctx.data.read().await;
+        let store = x.get::<Store>().unwrap();
+
+        store.history().find_one(kind, uid).await?
+    };
+
+    let url = match history {
+        Some(history) => Some(AudioSource::from_history(history).metadata().url.clone()),
+        // 재생 기록을 남기기 전에 누를 수도 있음
+        None => kind.provider().url(uid),
+    };
+
+    Ok(url.filter(|x| !x.is_empty()))
 }
 
 pub async fn play(
     ctx: &Context,
     guild_id: GuildId,
     voice_channel_id: ChannelId,
+    user_id: UserId,
     Parameter {
         url,
         kind,
         volume,
         play_count,
     }: Parameter,
-) -> crate::Result<(AudioMetadata, f32, Option<MessageId>)> {
+) -> crate::Result<(AudioMetadata, Volume, Option<MessageId>, Uuid)> {
     let handler = get_voice_handler(ctx, guild_id, voice_channel_id).await?;
     let mut handler = handler.lock().await;
 
     let mut x = ctx.data.write().await;
 
+    check_play_count(&x, guild_id, play_count).await?;
+
     let is_repeat = play_count.unwrap_or(1) >= 2;
 
-    let uid = match kind {
-        PlayableKind::YouTube => ytdl::parse_vid(url.parse().unwrap()),
-        PlayableKind::SoundCloud => {
-            if url.starts_with("https://api-v2.soundcloud.com/tracks/") {
-                url.replace("https://api-v2.soundcloud.com/tracks/", "")
-            } else {
-                let cfg = x.get::<Cfg>().unwrap();
-                let store = x.get::<Store>().unwrap();
-
-                let sc_client_id = store.elgua_cfg().get(CfgKey::SoundCloudApiKey).await?;
-                let sc_client_id = match sc_client_id.as_deref() {
-                    Some(r) => r,
-                    None => &cfg.soundcloud_client_id,
-                };
-
-                scdl::get_track(sc_client_id, &url).await?.id.to_string()
-            }
-        }
-    };
+    let credentials = get_credentials(&x).await?;
 
-    let history = {
+    let uid = kind.provider().parse_id(&credentials, &url).await?;
+
+    let (history, guild_history, history_channel_id) = {
         let store = x.get::<Store>().unwrap();
-        store.history().find_one(kind.into(), &uid).await?
+        let history = store.history().find_one(kind, &uid).await?;
+
+        let guild_history = match &history {
+            Some(history) => {
+                store
+                    .history()
+                    .get_guild_history(guild_id.get(), history.id)
+                    .await?
+            }
+            None => None,
+        };
+
+        let history_channel_id = store.guild_cfg().get(guild_id).await?.history_channel_id;
+
+        (
+            history,
+            guild_history.unwrap_or_default(),
+            history_channel_id,
+        )
     };
 
-    let (volume, prev_message_id) = {
-        match volume {
-            Some(volume) => (volume, None),
-            None => match &history {
-                Some(history) => (
-                    history.volume as f32 / 100.0,
-                    history.message_id.map(MessageId::new),
-                ),
-                None => (0.05, None),
-            },
+    let (volume, prev_message_id) = match volume {
+        Some(volume) => (Volume::Fixed(volume), None),
+        None => {
+            let volume = match guild_history.volume {
+                Some(volume) => Volume::Fixed(volume as f32 / 100.0),
+                None => Volume::Auto(get_auto_volume(&x, guild_id, kind, &uid).await?),
+            };
+
+            // 재생 기록 채널을 바꿨으면 이전 채널의 메세지는 지우지 않음
+            let prev_message_id = guild_history
+                .message_id
+                .filter(|_| guild_history.message_channel_id == history_channel_id.map(|x| x.get()))
+                .map(MessageId::new);
+
+            (volume, prev_message_id)
         }
     };
 
-    let audio_source = {
-        let cfg = x.get::<Cfg>().unwrap();
-
-        let r = match kind {
-            PlayableKind::YouTube => AudioSource::from_youtube(&cfg.youtube_api_key, &uid).await,
-            PlayableKind::SoundCloud => {
-                let cfg = x.get::<Cfg>().unwrap();
-                let store = x.get::<Store>().unwrap();
-
-                let sc_client_id = store.elgua_cfg().get(CfgKey::SoundCloudApiKey).await?;
-                let sc_client_id = match sc_client_id.as_deref() {
-                    Some(r) => r,
-                    None => &cfg.soundcloud_client_id,
-                };
-
-                AudioSource::from_soundcloud(sc_client_id, &url).await
-            }
-        };
-
-        match r {
-            Ok(r) => r,
-            Err(err) => {
-                let is_api_error = matches!(
-                    err,
-                    AudioSourceError::YouTubeApiError(_) | AudioSourceError::SoundCloudApiError(_)
-                );
-
-                match (history, is_api_error) {
-                    (Some(history), true) => AudioSource::from_history(history),
-                    _ => return Err(err.into()),
-                }
-            }
-        }
+    let audio_source = match AudioSource::new(&credentials, kind, &uid, &url).await {
+        Ok(r) => r,
+        Err(err) => match (history, err.is_api_error()) {
+            (Some(history), true) => AudioSource::from_history(history),
+            _ => return Err(err.into()),
+        },
     };
     let audio_metadata = audio_source.metadata().clone();
 
     if is_repeat {
         if let Some(duration) = audio_metadata.duration {
-            const M10: u64 = 10 * 60;
-            if duration > Duration::from_secs(M10) {
-                return Err(crate::error::Error::CustomError(
-                    "10분 이상의 음악은 반복 재생할 수 없습니다".to_owned(),
-                ));
+            let repeat_max_minutes = get_policy(&x, guild_id, CfgKey::RepeatMaxMinutes).await?;
+
+            if duration > Duration::from_secs(repeat_max_minutes * 60) {
+                return Err(crate::error::Error::CustomError(format!(
+                    "{repeat_max_minutes}분 이상의 음악은 반복 재생할 수 없습니다"
+                )));
             }
         } else {
             return Err(crate::error::Error::CustomError(
@@ -172,13 +189,31 @@ pub async fn play(
         }
     }
 
-    let mut source = audio_source.get_source().await?;
+    let filter = x
+        .get::<ActiveFilter>()
+        .and_then(|x| x.get(&guild_id))
+        .map(|x| x.filter)
+        .unwrap_or_default();
+
+    let mut source = audio_source
+        .get_filtered_source(filter, Duration::ZERO)
+        .await?;
 
     log::debug!("playable: {}", source.is_playable());
 
-    handler.stop();
+    let crossfade = Duration::from_millis(x.get::<Cfg>().unwrap().crossfade_ms);
+
+    // 겹쳐서 재생하는 동안 이전 음악은 소리를 줄이다가 중지함
+    let prev_track = x
+        .get::<Track>()
+        .and_then(|x| x.get(&guild_id))
+        .map(|Track(_, track)| track.clone());
+
+    if crossfade.is_zero() {
+        handler.stop();
+    }
 
-    let mut track = handler.play_only_input(source);
+    let mut track = handler.play_input(source);
 
     let mut try_count = 0;
 
@@ -199,7 +234,13 @@ pub async fn play(
 
         try_count += 1;
 
-        let play_result = [track.set_volume(volume), track.play()]
+        let initial_volume = if crossfade.is_zero() {
+            volume.get()
+        } else {
+            0.0
+        };
+
+        let play_result = [track.set_volume(initial_volume), track.play()]
             .into_iter()
             .collect::<Result<(), _>>();
 
@@ -220,8 +261,11 @@ pub async fn play(
 
             PlayMode::End => {
                 // source = audio_source.get_source(is_repeat).await?;
-                source = audio_source.get_source().await?;
-                track = handler.play_only_input(source);
+                source = audio_source
+                    .get_filtered_source(filter, Duration::ZERO)
+                    .await?;
+                track.stop().ok();
+                track = handler.play_input(source);
             }
 
             _ => {}
@@ -229,9 +273,221 @@ pub async fn play(
     }
 
     log::info!("url = {}", audio_metadata.url);
-    log::info!("volume = {}", volume);
+    log::info!("volume = {volume:?}");
 
-    x.insert::<Track>(Track(audio_metadata.clone(), track));
+    if !crossfade.is_zero() {
+        fade::fade_in(track.clone(), volume.get(), crossfade);
+
+        if let Some(prev_track) = prev_track {
+            fade::fade_out(prev_track, crossfade);
+        }
+    }
+
+    add_track_events(ctx, guild_id, voice_channel_id, &track)?;
+
+    let track_uuid = track.uuid();
+
+    x.entry::<Track>()
+        .or_default()
+        .insert(guild_id, Track(audio_metadata.clone(), track));
+
+    x.entry::<Requester>()
+        .or_default()
+        .insert(guild_id, user_id);
+
+    if let Some(active_filter) = x
+        .get_mut::<ActiveFilter>()
+        .and_then(|x| x.get_mut(&guild_id))
+    {
+        active_filter.offset = Duration::ZERO;
+    }
+
+    super::idle::cancel(&mut x, guild_id);
+
+    tokio::spawn(clean_up_cache(ctx.clone(), audio_metadata.clone()));
+    tokio::spawn(measure_loudness(ctx.clone(), audio_metadata.clone()));
+
+    Ok((audio_metadata.clone(), volume, prev_message_id, track_uuid))
+}
 
-    Ok((audio_metadata.clone(), volume, prev_message_id))
+pub(super) fn add_track_events(
+    ctx: &Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+    track: &TrackHandle,
+) -> Result<(), ControlError> {
+    // 재생이 끝나면 대기열의 다음 음악을 재생함
+    track.add_event(
+        songbird::Event::Track(TrackEvent::End),
+        AutoAdvance::new(ctx.clone(), guild_id, voice_channel_id, track.uuid()),
+    )?;
+
+    track.add_event(
+        songbird::Event::Track(TrackEvent::End),
+        RecordPlayEnd {
+            ctx: ctx.clone(),
+            uuid: track.uuid(),
+        },
+    )?;
+
+    // 반복하면 필터를 적용한 음악도 처음부터 다시 재생함
+    track.add_event(
+        songbird::Event::Track(TrackEvent::Loop),
+        ResetFilterOffset::new(ctx.clone(), guild_id, track.uuid()),
+    )?;
+
+    Ok(())
+}
+
+/// 측정한 음량이 없으면 기본 소리 크기를 사용함
+async fn get_auto_volume(
+    x: &TypeMap,
+    guild_id: GuildId,
+    kind: AudioSourceKind,
+    uid: &str,
+) -> crate::Result<f32> {
+    let cfg = x.get::<Cfg>().unwrap();
+    let store = x.get::<Store>().unwrap();
+
+    let default_volume = get_policy(x, guild_id, CfgKey::DefaultVolume).await? as f32 / 100.0;
+
+    let volume = store
+        .loudness()
+        .get(kind, uid)
+        .await?
+        .map(|integrated| loudness::normalize(integrated, cfg.target_loudness, default_volume))
+        .unwrap_or(default_volume);
+
+    Ok(volume)
+}
+
+/// `/config`로 정한 서버의 설정. 정하지 않았으면 기본값
+pub(super) async fn get_policy(x: &TypeMap, guild_id: GuildId, key: CfgKey) -> crate::Result<u64> {
+    let store = x.get::<Store>().unwrap();
+
+    let value = store
+        .elgua_cfg()
+        .get_or_default(key, Some(guild_id))
+        .await?
+        .unwrap_or_default();
+
+    Ok(value)
+}
+
+/// 서버에서 정한 최대 재생 횟수보다 많이 반복하지 않도록 함
+pub(super) async fn check_play_count(
+    x: &TypeMap,
+    guild_id: GuildId,
+    play_count: Option<usize>,
+) -> crate::Result<()> {
+    let Some(play_count) = play_count else {
+        return Ok(());
+    };
+
+    let play_count_max = get_policy(x, guild_id, CfgKey::PlayCountMax).await?;
+
+    if play_count as u64 > play_count_max {
+        return Err(crate::error::Error::CustomError(format!(
+            "재생 횟수는 {play_count_max}번까지 정할 수 있어요"
+        )));
+    }
+
+    Ok(())
+}
+
+/// 다 받은 음악의 음량을 측정해 두고 다음에 재생할 때부터 소리 크기를 맞춤
+async fn measure_loudness(ctx: Context, audio_metadata: AudioMetadata) {
+    let kind = audio_metadata.kind();
+    let id = audio_metadata.id;
+
+    progressive::wait(kind, &id).await;
+
+    let r = async {
+        // 측정하는 동안 ctx.data를 잠그지 않도록 store를 따로 꺼냄
+        let measured = {
+            let x = ctx.data.read().await;
+            let store = x.get::<Store>().unwrap();
+
+            store.loudness().get(kind, &id).await?.is_some()
+        };
+
+        if measured {
+            return Ok(());
+        }
+
+        let path = AudioCache::path(kind, &id);
+        let integrated = tokio::task::spawn_blocking(move || loudness::measure(&path))
+            .await
+            .map_err(io::Error::other)??;
+
+        let Some(integrated) = integrated else {
+            log::info!("skipped measuring silent {}/{id}", kind.as_str());
+            return Ok(());
+        };
+
+        log::info!("measured {}/{id}: {integrated:.1} LUFS", kind.as_str());
+
+        let x = ctx.data.read().await;
+        let store = x.get::<Store>().unwrap();
+
+        store.loudness().set(kind, &id, integrated).await?;
+
+        crate::Result::Ok(())
+    };
+
+    if let Err(err) = r.await {
+        log::error!("failed to measure loudness: {err}");
+    }
+}
+
+/// 재생한 음악의 캐시 사용 기록을 갱신하고 용량을 넘은 캐시를 지움
+async fn clean_up_cache(ctx: Context, audio_metadata: AudioMetadata) {
+    // 다 받은 다음에 파일 크기를 알 수 있음
+    // 받는 동안 다른 명령어를 막지 않도록 잠그기 전에 기다림
+    progressive::wait(audio_metadata.kind(), &audio_metadata.id).await;
+
+    let x = ctx.data.read().await;
+    let cfg = x.get::<Cfg>().unwrap();
+    let store = x.get::<Store>().unwrap();
+
+    let playing = x
+        .get::<Track>()
+        .into_iter()
+        .flat_map(|x| x.values())
+        .map(|Track(audio_metadata, _)| (audio_metadata.kind(), audio_metadata.id.clone()))
+        .chain([(audio_metadata.kind(), audio_metadata.id.clone())])
+        .collect::<HashSet<_>>();
+
+    let r = async {
+        AudioCache::touch(store, audio_metadata.kind(), &audio_metadata.id).await?;
+        AudioCache::evict(store, cfg.cache_max_bytes, &playing).await
+    };
+
+    if let Err(err) = r.await {
+        log::error!("failed to clean up cache: {err}");
+    }
+}
+
+/// 재생이 끝나거나 중지되면 재생 기록에 끝난 시간을 남기도록 함
+struct RecordPlayEnd {
+    ctx: Context,
+    uuid: Uuid,
+}
+
+#[async_trait::async_trait]
+impl songbird::EventHandler for RecordPlayEnd {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<songbird::Event> {
+        let event_tx = {
+            let x = self.ctx.data.read().await;
+            x.get::<EventSender>().cloned().unwrap()
+        };
+
+        let event = Event::End(self.uuid, Utc::now());
+
+        if let Err(err) = event_tx.send((self.ctx.clone(), event)).await {
+            log::error!("closed event channel: {err}");
+        }
+
+        None
+    }
 }
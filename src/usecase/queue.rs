@@ -0,0 +1,343 @@
+use chrono::Utc;
+use serenity::{
+    model::id::{ChannelId, GuildId, UserId},
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use songbird::{events::EventContext, tracks::PlayMode};
+use uuid::Uuid;
+
+use crate::{
+    audio::{AudioMetadata, AudioPlaylist},
+    error::Error,
+    event::{Event, EventSender},
+    store::{Store, TrackQueue, TRACK_QUEUE_MAX_LEN as MAX_LEN},
+    track::Track,
+};
+
+use super::play::{self, check_play_count, get_credentials};
+
+/// `/queue list`에서 보여줄 최대 갯수
+const LIST_LEN: usize = 10;
+
+/// 재생이 끝난 음악이 현재 음악일 때만 대기열의 다음 음악을 재생함
+///
+/// `/play`로 음악이 바뀌거나 `/stop`으로 중지된 경우에는 현재 음악이 아니므로 넘어가지 않음
+pub struct AutoAdvance {
+    ctx: Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+    uuid: Uuid,
+}
+
+impl AutoAdvance {
+    pub fn new(ctx: Context, guild_id: GuildId, voice_channel_id: ChannelId, uuid: Uuid) -> Self {
+        Self {
+            ctx,
+            guild_id,
+            voice_channel_id,
+            uuid,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl songbird::EventHandler for AutoAdvance {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<songbird::Event> {
+        let ctx = self.ctx.clone();
+        let guild_id = self.guild_id;
+        let voice_channel_id = self.voice_channel_id;
+        let uuid = self.uuid;
+
+        tokio::spawn(async move {
+            let is_current = {
+                let x = ctx.data.read().await;
+
+                x.get::<Track>()
+                    .and_then(|x| x.get(&guild_id))
+                    .map(|Track(_, track)| track.uuid() == uuid)
+                    .unwrap_or(false)
+            };
+
+            if !is_current {
+                return;
+            }
+
+            match play_next(&ctx, guild_id, voice_channel_id).await {
+                Ok(Some(_)) => {}
+
+                // 대기열이 비었으면 나가기 타이머를 시작함
+                Ok(None) => super::idle::start_idle_timer(&ctx, guild_id).await,
+
+                Err(err) => log::error!("{err}"),
+            }
+        });
+
+        None
+    }
+}
+
+pub async fn is_playing(ctx: &Context, guild_id: GuildId) -> bool {
+    let x = ctx.data.read().await;
+
+    let Some(Track(_, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) else {
+        return false;
+    };
+
+    let play_state = track
+        .get_info()
+        .await
+        .map(|x| x.playing)
+        .unwrap_or(PlayMode::End);
+
+    matches!(play_state, PlayMode::Play | PlayMode::Pause)
+}
+
+/// 대기열에서 다음 음악을 꺼내서 재생함
+///
+/// 재생할 수 없는 음악은 건너뜀
+pub async fn play_next(
+    ctx: &Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+) -> crate::Result<Option<AudioMetadata>> {
+    let event_tx = {
+        let x = ctx.data.read().await;
+        x.get::<EventSender>().cloned().unwrap()
+    };
+
+    loop {
+        let next = {
+            let x = ctx.data.read().await;
+            let store = x.get::<Store>().unwrap();
+
+            store.track_queue().pop_front(guild_id.get()).await?
+        };
+
+        let Some(next) = next else {
+            return Ok(None);
+        };
+
+        let parameter = play::Parameter::new(
+            next.kind,
+            next.url.clone(),
+            next.volume.map(|x| x as f32 / 100.0),
+            next.play_count,
+        );
+
+        let user_id = UserId::new(next.user_id);
+
+        match play::play(ctx, guild_id, voice_channel_id, user_id, parameter).await {
+            Ok((audio_metadata, volume, prev_message_id, track_uuid)) => {
+                let event = Event::Play(
+                    audio_metadata.clone(),
+                    volume,
+                    next.play_count,
+                    user_id,
+                    guild_id,
+                    prev_message_id,
+                    track_uuid,
+                );
+
+                if let Err(err) = event_tx.send((ctx.clone(), event)).await {
+                    log::error!("closed event channel: {err}");
+                }
+
+                return Ok(Some(audio_metadata));
+            }
+
+            Err(err) => {
+                log::error!("failed to play {}: {err}", next.url);
+            }
+        }
+    }
+}
+
+pub async fn add(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    play::Parameter {
+        url,
+        kind,
+        volume,
+        play_count,
+    }: play::Parameter,
+) -> crate::Result<(TrackQueue, usize)> {
+    let x = ctx.data.read().await;
+
+    check_play_count(&x, guild_id, play_count).await?;
+
+    let credentials = get_credentials(&x).await?;
+    let provider = kind.provider();
+
+    let id = provider.parse_id(&credentials, &url).await?;
+    let audio_metadata = provider.get_metadata(&credentials, &id, &url).await?;
+
+    let track_queue = TrackQueue {
+        id: 0,
+        guild_id: guild_id.get(),
+        title: audio_metadata.title,
+        kind,
+        uid: audio_metadata.id,
+        url: audio_metadata.url,
+        user_id: user_id.get(),
+        volume: volume.map(|x| (x * 100.0) as u8),
+        play_count,
+        created_at: Utc::now(),
+    };
+
+    let store = x.get::<Store>().unwrap();
+    let position = store.track_queue().push_back(&track_queue).await?;
+
+    Ok((track_queue, position))
+}
+
+/// 재생목록의 음악을 순서대로 대기열에 추가함
+///
+/// 대기열이 가득 차면 나머지는 추가하지 않음
+///
+/// returns (playlist, 추가된 음악 수)
+pub async fn add_playlist(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    play::Parameter {
+        url,
+        kind,
+        volume,
+        play_count,
+    }: play::Parameter,
+) -> crate::Result<(AudioPlaylist, usize)> {
+    let x = ctx.data.read().await;
+
+    check_play_count(&x, guild_id, play_count).await?;
+
+    let credentials = get_credentials(&x).await?;
+
+    let playlist = kind
+        .provider()
+        .get_playlist(&credentials, &url, MAX_LEN)
+        .await?;
+
+    let store = x.get::<Store>().unwrap();
+    let mut added = 0;
+
+    for audio_metadata in &playlist.items {
+        let track_queue = TrackQueue {
+            id: 0,
+            guild_id: guild_id.get(),
+            title: audio_metadata.title.clone(),
+            kind,
+            uid: audio_metadata.id.clone(),
+            url: audio_metadata.url.clone(),
+            user_id: user_id.get(),
+            volume: volume.map(|x| (x * 100.0) as u8),
+            play_count,
+            created_at: Utc::now(),
+        };
+
+        match store.track_queue().push_back(&track_queue).await {
+            Ok(_) => added += 1,
+            // 대기열이 가득 참
+            Err(Error::CustomError(_)) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok((playlist, added))
+}
+
+pub async fn list(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let queue = store.track_queue().list(guild_id.get()).await?;
+
+    if queue.is_empty() {
+        return Ok("대기열이 비어 있어요".to_string());
+    }
+
+    let mut r = MessageBuilder::new()
+        .push("대기열: ")
+        .push(queue.len().to_string())
+        .push("곡")
+        .to_owned();
+
+    for (i, x) in queue.iter().take(LIST_LEN).enumerate() {
+        r.push("\n")
+            .push((i + 1).to_string())
+            .push(". ")
+            .push_named_link(&x.title, &x.url);
+    }
+
+    if queue.len() > LIST_LEN {
+        r.push("\n외 ")
+            .push((queue.len() - LIST_LEN).to_string())
+            .push("곡");
+    }
+
+    Ok(r.build())
+}
+
+pub async fn remove(ctx: &Context, guild_id: GuildId, index: usize) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    match store.track_queue().remove(guild_id.get(), index).await? {
+        Some(track_queue) => Ok(MessageBuilder::new()
+            .push("대기열에서 삭제했어요 : ")
+            .push_named_link(&track_queue.title, &track_queue.url)
+            .build()),
+        None => Ok(format!("대기열에 {index}번째 음악이 없어요")),
+    }
+}
+
+pub async fn move_to(
+    ctx: &Context,
+    guild_id: GuildId,
+    from: usize,
+    to: usize,
+) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    match store
+        .track_queue()
+        .move_to(guild_id.get(), from, to)
+        .await?
+    {
+        Some((track_queue, to)) => Ok(MessageBuilder::new()
+            .push_named_link(&track_queue.title, &track_queue.url)
+            .push(format!(" : {from}번째 → {to}번째"))
+            .build()),
+        None => Ok(format!("대기열에 {from}번째 음악이 없어요")),
+    }
+}
+
+pub async fn clear(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let count = store.track_queue().clear(guild_id.get()).await?;
+
+    Ok(format!("대기열을 비웠어요 ({count}곡)"))
+}
+
+pub async fn skip(
+    ctx: &Context,
+    guild_id: GuildId,
+    voice_channel_id: ChannelId,
+) -> crate::Result<String> {
+    match play_next(ctx, guild_id, voice_channel_id).await? {
+        Some(audio_metadata) => Ok(MessageBuilder::new()
+            .push("다음 음악을 재생해요 : ")
+            .push_named_link(&audio_metadata.title, &audio_metadata.url)
+            .build()),
+        None => {
+            super::stop(ctx, guild_id).await?;
+
+            Ok("대기열에 다음 음악이 없어요".to_string())
+        }
+    }
+}
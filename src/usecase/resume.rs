@@ -0,0 +1,37 @@
+use serenity::{
+    model::id::GuildId,
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use songbird::tracks::PlayMode;
+
+use crate::track::Track;
+
+pub async fn resume(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+
+    if let Some(Track(audio_metadata, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) {
+        let play_state = track
+            .get_info()
+            .await
+            .map(|x| x.playing)
+            .unwrap_or(PlayMode::End);
+
+        match play_state {
+            PlayMode::Pause => {
+                track.play()?;
+
+                return Ok(MessageBuilder::new()
+                    .push("다시 재생해요 : ")
+                    .push_named_link(&audio_metadata.title, &audio_metadata.url)
+                    .build());
+            }
+
+            PlayMode::Play => return Ok("이미 재생 중이에요".to_string()),
+
+            _ => {}
+        }
+    }
+
+    Ok("재생 중인 음악이 없어요".to_string())
+}
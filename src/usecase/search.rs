@@ -0,0 +1,34 @@
+use serenity::{model::id::GuildId, prelude::Context};
+
+use crate::{
+    audio::{AudioMetadata, AudioSource},
+    store::Store,
+};
+
+/// 버튼은 한 줄에 5개까지만 넣을 수 있어서 한 페이지에 5개씩 보여줌
+pub const PER_PAGE: usize = 5;
+
+/// 이 서버에서 재생한 기록만 찾음
+///
+/// returns (audio metadatas, total)
+pub async fn search(
+    ctx: &Context,
+    guild_id: GuildId,
+    keyword: &str,
+    page: usize,
+) -> crate::Result<(Vec<AudioMetadata>, usize)> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let (histories, total) = store
+        .history()
+        .search(keyword, guild_id.get(), page, PER_PAGE)
+        .await?;
+
+    let audio_metadatas = histories
+        .into_iter()
+        .map(|x| AudioSource::from_history(x).metadata().clone())
+        .collect();
+
+    Ok((audio_metadatas, total))
+}
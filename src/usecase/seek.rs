@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use serenity::{
+    model::id::GuildId,
+    prelude::Context,
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use songbird::tracks::PlayMode;
+
+use crate::{
+    audio::{filter::Filter, AudioMetadata},
+    track::{ActiveFilter, Track},
+    util::time::seperate_duration,
+};
+
+pub async fn seek(ctx: &Context, guild_id: GuildId, position: Duration) -> crate::Result<String> {
+    let filter = {
+        let x = ctx.data.read().await;
+
+        let Some(Track(audio_metadata, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id))
+        else {
+            return Ok("재생 중인 음악이 없어요".to_string());
+        };
+
+        let play_state = track
+            .get_info()
+            .await
+            .map(|x| x.playing)
+            .unwrap_or(PlayMode::End);
+
+        if !matches!(play_state, PlayMode::Play | PlayMode::Pause) {
+            return Ok("재생 중인 음악이 없어요".to_string());
+        }
+
+        if let Some(duration) = audio_metadata.duration {
+            if position >= duration {
+                return Ok(format!(
+                    "음악의 길이({})보다 앞으로 이동할 수 없어요",
+                    seperate_duration(duration)
+                ));
+            }
+        }
+
+        let filter = x
+            .get::<ActiveFilter>()
+            .and_then(|x| x.get(&guild_id))
+            .map(|x| x.filter)
+            .unwrap_or_default();
+
+        if filter == Filter::Off {
+            let position = track.seek_async(position).await?;
+
+            return Ok(seeked(audio_metadata, position));
+        }
+
+        filter
+    };
+
+    // 필터를 적용한 음악은 이동할 위치부터 다시 재생함
+    match super::filter::restart(ctx, guild_id, filter, position).await? {
+        Some(audio_metadata) => Ok(seeked(&audio_metadata, position)),
+        None => Ok("재생 중인 음악이 없어요".to_string()),
+    }
+}
+
+fn seeked(audio_metadata: &AudioMetadata, position: Duration) -> String {
+    let mut r = MessageBuilder::new()
+        .push_named_link(&audio_metadata.title, &audio_metadata.url)
+        .push("\n재생 시간: ")
+        .to_owned();
+
+    if position.as_secs() == 0 {
+        r.push("처음");
+    } else {
+        r.push(seperate_duration(position).to_string());
+    }
+
+    r.push("(으)로 이동했어요").build()
+}
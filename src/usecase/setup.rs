@@ -0,0 +1,43 @@
+use serenity::{
+    model::id::{ChannelId, GuildId},
+    prelude::Context,
+    utils::MessageBuilder,
+};
+
+use crate::store::Store;
+
+/// 입력하지 않은 설정은 그대로 두고 현재 설정을 보여줌
+pub async fn setup(
+    ctx: &Context,
+    guild_id: GuildId,
+    voice_channel_id: Option<ChannelId>,
+    history_channel_id: Option<ChannelId>,
+) -> crate::Result<String> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let mut guild_cfg = store.guild_cfg().get(guild_id).await?;
+
+    if voice_channel_id.is_some() || history_channel_id.is_some() {
+        guild_cfg.voice_channel_id = voice_channel_id.or(guild_cfg.voice_channel_id);
+        guild_cfg.history_channel_id = history_channel_id.or(guild_cfg.history_channel_id);
+
+        store.guild_cfg().add_or_update(&guild_cfg).await?;
+    }
+
+    let mut r = MessageBuilder::new();
+
+    r.push("음성 채널: ");
+    match guild_cfg.voice_channel_id {
+        Some(channel_id) => r.channel(channel_id),
+        None => r.push("없음"),
+    };
+
+    r.push("\n기록 채널: ");
+    match guild_cfg.history_channel_id {
+        Some(channel_id) => r.channel(channel_id),
+        None => r.push("없음"),
+    };
+
+    Ok(r.build())
+}
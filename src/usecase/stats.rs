@@ -0,0 +1,181 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::Context,
+};
+
+use crate::{
+    audio::{AudioMetadata, AudioSource, AudioSourceKind},
+    store::{Ranking, Store},
+};
+
+/// 버튼이 없어서 검색 결과보다 많이 보여줌
+pub const PER_PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Category {
+    Tracks,
+    Users,
+    Channels,
+    Kinds,
+}
+
+impl Category {
+    pub fn as_str(&self) -> &str {
+        use Category::*;
+
+        match self {
+            Tracks => "tracks",
+            Users => "users",
+            Channels => "channels",
+            Kinds => "kinds",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Category::*;
+
+        let r = match s {
+            "tracks" => Tracks,
+            "users" => Users,
+            "channels" => Channels,
+            "kinds" => Kinds,
+            _ => return Err(()),
+        };
+
+        Ok(r)
+    }
+}
+
+/// 집계할 기간
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    #[default]
+    All,
+}
+
+impl Period {
+    pub fn as_str(&self) -> &str {
+        use Period::*;
+
+        match self {
+            Day => "day",
+            Week => "week",
+            Month => "month",
+            All => "all",
+        }
+    }
+
+    fn since(&self) -> Option<DateTime<Utc>> {
+        use Period::*;
+
+        let duration = match self {
+            Day => Duration::days(1),
+            Week => Duration::weeks(1),
+            Month => Duration::days(30),
+            All => return None,
+        };
+
+        Some(Utc::now() - duration)
+    }
+}
+
+impl FromStr for Period {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Period::*;
+
+        let r = match s {
+            "day" => Day,
+            "week" => Week,
+            "month" => Month,
+            "all" => All,
+            _ => return Err(()),
+        };
+
+        Ok(r)
+    }
+}
+
+pub enum Stats {
+    Tracks(Vec<Ranking<AudioMetadata>>),
+    Users(Vec<Ranking<UserId>>),
+    Channels(Vec<Ranking<String>>),
+    Kinds(Vec<Ranking<AudioSourceKind>>),
+}
+
+/// returns (stats, total)
+pub async fn stats(
+    ctx: &Context,
+    guild_id: GuildId,
+    category: Category,
+    period: Period,
+    page: usize,
+) -> crate::Result<(Stats, usize)> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+    let stats = store.stats();
+
+    let guild_id = guild_id.get();
+    let since = period.since();
+    let offset = (page - 1) * PER_PAGE;
+
+    let r = match category {
+        Category::Tracks => {
+            let (rankings, total) = stats.top_tracks(guild_id, since, offset, PER_PAGE).await?;
+
+            let rankings = rankings
+                .into_iter()
+                .map(|x| Ranking {
+                    value: AudioSource::from_history(x.value).metadata().clone(),
+                    count: x.count,
+                })
+                .collect();
+
+            (Stats::Tracks(rankings), total)
+        }
+
+        Category::Users => {
+            let (rankings, total) = stats.top_users(guild_id, since, offset, PER_PAGE).await?;
+
+            let rankings = rankings
+                .into_iter()
+                .map(|x| Ranking {
+                    value: UserId::new(x.value),
+                    count: x.count,
+                })
+                .collect();
+
+            (Stats::Users(rankings), total)
+        }
+
+        Category::Channels => {
+            let (rankings, total) = stats
+                .top_channels(guild_id, since, offset, PER_PAGE)
+                .await?;
+
+            (Stats::Channels(rankings), total)
+        }
+
+        // 음악을 가져온 곳은 많지 않아서 한 페이지에 모두 보여줌
+        Category::Kinds => {
+            let rankings = stats.kinds(guild_id, since).await?;
+
+            let total = rankings.len();
+
+            (Stats::Kinds(rankings), total)
+        }
+    };
+
+    Ok(r)
+}
@@ -1,12 +1,25 @@
-use serenity::prelude::Context;
+use std::time::Duration;
+
+use serenity::{model::id::GuildId, prelude::Context};
 use songbird::tracks::PlayMode;
 
-use crate::track::Track;
+use crate::{cfg::Cfg, track::Track};
+
+pub async fn stop(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
+    // 중지한 음악은 지워서 대기열의 다음 음악이 재생되지 않도록 함
+    let (track, crossfade) = {
+        let mut x = ctx.data.write().await;
+        let crossfade = Duration::from_millis(x.get::<Cfg>().unwrap().crossfade_ms);
+
+        (
+            x.get_mut::<Track>().and_then(|x| x.remove(&guild_id)),
+            crossfade,
+        )
+    };
 
-pub async fn stop(ctx: &Context) -> crate::Result<String> {
-    let x = ctx.data.read().await;
+    super::idle::start_idle_timer(ctx, guild_id).await;
 
-    if let Some(Track(_, track)) = x.get::<Track>() {
+    if let Some(Track(_, track)) = track {
         let play_state = track
             .get_info()
             .await
@@ -14,7 +27,11 @@ pub async fn stop(ctx: &Context) -> crate::Result<String> {
             .unwrap_or(PlayMode::End);
 
         if let PlayMode::Play | PlayMode::Pause = play_state {
-            track.stop()?;
+            if crossfade.is_zero() {
+                track.stop()?;
+            } else {
+                super::fade::fade_out(track, crossfade);
+            }
 
             return Ok("재생 중인 음악이 중지되었어요".to_string());
         }
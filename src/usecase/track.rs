@@ -1,15 +1,20 @@
 use serenity::{
+    model::id::GuildId,
     prelude::Context,
     utils::{EmbedMessageBuilding, MessageBuilder},
 };
 use songbird::tracks::{LoopState, PlayMode, TrackState};
 
-use crate::{track::Track, util::time::seperate_duration};
+use crate::{
+    audio::filter::Filter,
+    track::{ActiveFilter, Track},
+    util::time::seperate_duration,
+};
 
-pub async fn track(ctx: &Context) -> crate::Result<String> {
+pub async fn track(ctx: &Context, guild_id: GuildId) -> crate::Result<String> {
     let x = ctx.data.read().await;
 
-    if let Some(Track(audio_metadata, track)) = x.get::<Track>() {
+    if let Some(Track(audio_metadata, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) {
         let play_info = track.get_info().await.ok();
 
         let play_state = play_info
@@ -26,7 +31,14 @@ pub async fn track(ctx: &Context) -> crate::Result<String> {
                 ..
             } = play_info.unwrap();
 
-            let position = seperate_duration(position);
+            let active_filter = x
+                .get::<ActiveFilter>()
+                .and_then(|x| x.get(&guild_id))
+                .copied()
+                .unwrap_or_default();
+
+            // 필터로 빠르거나 느리게 재생해도 원래 음악의 재생 시간을 보여줌
+            let position = seperate_duration(active_filter.source_position(position));
             let position_with_looped = seperate_duration(play_time);
 
             let mut r = MessageBuilder::new()
@@ -41,6 +53,10 @@ pub async fn track(ctx: &Context) -> crate::Result<String> {
                 r.push(" - ").push(position_with_looped.to_string());
             }
 
+            if active_filter.filter != Filter::Off {
+                r.push("\n필터: ").push(active_filter.filter.name());
+            }
+
             // match &audio_metadata.duration {
             //     Either::Left(x) => {
             //         let total = seperate_duration(*x);
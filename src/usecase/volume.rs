@@ -1,6 +1,6 @@
 use serenity::{
     all::EditMessage,
-    model::id::{ChannelId, MessageId},
+    model::id::{ChannelId, GuildId, MessageId},
     prelude::*,
     utils::MessageBuilder,
 };
@@ -10,12 +10,13 @@ use crate::{store::Store, track::Track};
 
 pub async fn volume(
     ctx: &Context,
-    history_channel_id: ChannelId,
+    guild_id: GuildId,
+    history_channel_id: Option<ChannelId>,
     volume: f32,
 ) -> crate::Result<String> {
     let x = ctx.data.read().await;
 
-    if let Some(Track(audio_metadata, track)) = x.get::<Track>() {
+    if let Some(Track(audio_metadata, track)) = x.get::<Track>().and_then(|x| x.get(&guild_id)) {
         let play_state = track
             .get_info()
             .await
@@ -31,11 +32,27 @@ pub async fn volume(
 
             let history = store
                 .history()
-                .find_one(audio_metadata.kind().into(), &audio_metadata.id)
+                .find_one(audio_metadata.kind(), &audio_metadata.id)
                 .await?
                 .unwrap();
 
-            if let Some(message_id) = history.message_id.map(MessageId::new) {
+            let guild_history = store
+                .history()
+                .get_guild_history(guild_id.get(), history.id)
+                .await?
+                .unwrap_or_default();
+
+            let message = guild_history
+                .message_channel_id
+                .zip(guild_history.message_id)
+                .filter(|(channel_id, _)| history_channel_id.map(|x| x.get()) == Some(*channel_id));
+
+            if let Some((history_channel_id, message_id)) = message {
+                let (history_channel_id, message_id) = (
+                    ChannelId::new(history_channel_id),
+                    MessageId::new(message_id),
+                );
+
                 if let Ok(mut message) = ctx.http.get_message(history_channel_id, message_id).await
                 {
                     let mut embed = message.embeds.first().cloned().unwrap();
@@ -57,7 +74,7 @@ pub async fn volume(
 
             store
                 .history()
-                .update_volume(audio_metadata.kind().into(), &audio_metadata.id, volume_u8)
+                .update_guild_volume(guild_id.get(), history.id, volume_u8)
                 .await?;
 
             return Ok(MessageBuilder::new()
@@ -0,0 +1,339 @@
+//! 다른 사람이 재생한 음악을 중지하거나 바꿀 때 음성 채널에 있는 사람들이 투표함
+//!
+//! 투표는 서버마다 하나씩만 할 수 있음
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serenity::{
+    model::id::{ChannelId, GuildId, UserId},
+    prelude::{Context, TypeMapKey},
+    utils::{EmbedMessageBuilding, MessageBuilder},
+};
+use uuid::Uuid;
+
+use crate::{
+    event::{Event, EventSender},
+    store::{CfgKey, Store},
+    track::Track,
+};
+
+use super::play;
+
+pub enum VoteAction {
+    Stop,
+    /// 재생 중인 음악 대신 재생함
+    Replace {
+        voice_channel_id: ChannelId,
+        parameter: play::Parameter,
+    },
+}
+
+impl VoteAction {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Stop => "중지",
+            Self::Replace { .. } => "다른 음악으로 바꾸기",
+        }
+    }
+}
+
+pub struct Vote {
+    id: u64,
+    /// 투표를 시작한 사람. 음성 채널에 있는 동안 찬성한 것으로 셈
+    user_id: UserId,
+    action: VoteAction,
+    /// 투표를 시작할 때 재생 중이던 음악
+    track_uuid: Option<Uuid>,
+    title: String,
+    yes: HashSet<UserId>,
+    no: HashSet<UserId>,
+    ratio: u64,
+    timeout: Duration,
+}
+
+/// 서버별로 진행 중인 투표
+pub struct Votes;
+
+impl TypeMapKey for Votes {
+    type Value = HashMap<GuildId, Vote>;
+}
+
+static NEXT_VOTE_ID: AtomicU64 = AtomicU64::new(0);
+
+pub enum Started {
+    /// 통과하는 데 다른 사람의 찬성이 필요 없음
+    Passed(Box<Vote>),
+    /// (투표 id, 투표 메세지, 투표 시간)
+    Voting(u64, String, Duration),
+    /// 이미 진행 중인 투표가 있음
+    Running,
+    /// 투표를 시작한 사람이 음성 채널에 없음
+    NotListening,
+}
+
+pub enum Cast {
+    Voting(String),
+    Passed(Box<Vote>, String),
+    Rejected(String),
+    NotListening,
+    /// 끝났거나 다른 투표
+    Closed,
+}
+
+/// 통과하려면 찬성이 듣고 있는 사람의 ratio%보다 많아야 함
+fn required(listeners: usize, ratio: u64) -> usize {
+    listeners * ratio as usize / 100 + 1
+}
+
+pub async fn is_enabled(ctx: &Context, guild_id: GuildId) -> crate::Result<bool> {
+    let x = ctx.data.read().await;
+    let store = x.get::<Store>().unwrap();
+
+    let enabled = store
+        .elgua_cfg()
+        .get_or_default(CfgKey::Vote, Some(guild_id))
+        .await?
+        .unwrap_or(false);
+
+    Ok(enabled)
+}
+
+pub async fn start(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    action: VoteAction,
+) -> crate::Result<Started> {
+    let mut x = ctx.data.write().await;
+
+    if x.get::<Votes>().is_some_and(|x| x.contains_key(&guild_id)) {
+        return Ok(Started::Running);
+    }
+
+    let listeners = super::idle::listeners(ctx, guild_id).unwrap_or_default();
+
+    if !listeners.contains(&user_id) {
+        return Ok(Started::NotListening);
+    }
+
+    let ratio = play::get_policy(&x, guild_id, CfgKey::VoteRatio).await?;
+    let timeout = play::get_policy(&x, guild_id, CfgKey::VoteTimeoutSecs).await?;
+
+    let (track_uuid, title) = match x.get::<Track>().and_then(|x| x.get(&guild_id)) {
+        Some(Track(audio_metadata, track)) => (Some(track.uuid()), audio_metadata.title.clone()),
+        None => (None, String::new()),
+    };
+
+    let vote = Vote {
+        id: NEXT_VOTE_ID.fetch_add(1, Ordering::Relaxed),
+        user_id,
+        action,
+        track_uuid,
+        title,
+        yes: HashSet::from([user_id]),
+        no: HashSet::new(),
+        ratio,
+        timeout: Duration::from_secs(timeout),
+    };
+
+    if tally(&vote, &listeners).is_passed() {
+        return Ok(Started::Passed(Box::new(vote)));
+    }
+
+    let r = Started::Voting(vote.id, render(&vote, &listeners), vote.timeout);
+
+    x.entry::<Votes>().or_default().insert(guild_id, vote);
+
+    Ok(r)
+}
+
+/// 음성 채널에 있는 사람만 투표할 수 있고 다시 투표하면 바꿈
+pub async fn cast(
+    ctx: &Context,
+    guild_id: GuildId,
+    vote_id: u64,
+    user_id: UserId,
+    agree: bool,
+) -> crate::Result<Cast> {
+    let mut x = ctx.data.write().await;
+
+    let Some(votes) = x.get_mut::<Votes>() else {
+        return Ok(Cast::Closed);
+    };
+
+    let Some(vote) = votes.get_mut(&guild_id).filter(|x| x.id == vote_id) else {
+        return Ok(Cast::Closed);
+    };
+
+    // 투표하는 동안 나가거나 들어온 사람이 있을 수 있어서 투표할 때마다 다시 셈
+    let listeners = super::idle::listeners(ctx, guild_id).unwrap_or_default();
+
+    if !listeners.contains(&user_id) {
+        return Ok(Cast::NotListening);
+    }
+
+    if agree {
+        vote.no.remove(&user_id);
+        vote.yes.insert(user_id);
+    } else {
+        vote.yes.remove(&user_id);
+        vote.no.insert(user_id);
+    }
+
+    let tally = tally(vote, &listeners);
+    let content = render(vote, &listeners);
+
+    if tally.is_passed() {
+        let vote = votes.remove(&guild_id).unwrap();
+
+        return Ok(Cast::Passed(Box::new(vote), content));
+    }
+
+    if tally.is_rejected() {
+        votes.remove(&guild_id);
+
+        return Ok(Cast::Rejected(content));
+    }
+
+    Ok(Cast::Voting(content))
+}
+
+/// 끝나지 않은 투표를 끝내고 마지막 투표 메세지를 돌려줌
+pub async fn expire(ctx: &Context, guild_id: GuildId, vote_id: u64) -> Option<String> {
+    let mut x = ctx.data.write().await;
+
+    let votes = x.get_mut::<Votes>()?;
+
+    if votes.get(&guild_id)?.id != vote_id {
+        return None;
+    }
+
+    let vote = votes.remove(&guild_id)?;
+    let listeners = super::idle::listeners(ctx, guild_id).unwrap_or_default();
+
+    Some(render(&vote, &listeners))
+}
+
+/// 통과한 투표를 실행함
+pub async fn run(ctx: &Context, guild_id: GuildId, vote: Vote) -> crate::Result<String> {
+    match vote.action {
+        VoteAction::Stop => {
+            let current_uuid = {
+                let x = ctx.data.read().await;
+
+                x.get::<Track>()
+                    .and_then(|x| x.get(&guild_id))
+                    .map(|Track(_, track)| track.uuid())
+            };
+
+            // 투표하는 동안 다른 음악으로 바뀌었으면 중지하지 않음
+            if current_uuid != vote.track_uuid {
+                return Ok("투표한 음악이 이미 끝났어요".to_string());
+            }
+
+            super::stop(ctx, guild_id).await
+        }
+
+        VoteAction::Replace {
+            voice_channel_id,
+            parameter,
+        } => {
+            let play_count = parameter.play_count;
+
+            let (audio_metadata, volume, prev_message_id, track_uuid) =
+                play::play(ctx, guild_id, voice_channel_id, vote.user_id, parameter).await?;
+
+            let event_tx = ctx.data.read().await.get::<EventSender>().cloned().unwrap();
+
+            let event = Event::Play(
+                audio_metadata.clone(),
+                volume,
+                play_count,
+                vote.user_id,
+                guild_id,
+                prev_message_id,
+                track_uuid,
+            );
+
+            if let Err(err) = event_tx.send((ctx.clone(), event)).await {
+                log::error!("closed event channel: {err}");
+            }
+
+            Ok(MessageBuilder::new()
+                .push_named_link(&audio_metadata.title, &audio_metadata.url)
+                .push("\n소리 크기: ")
+                .push((volume.get() * 100.0).to_string())
+                .build())
+        }
+    }
+}
+
+struct Tally {
+    yes: usize,
+    no: usize,
+    required: usize,
+    voters: usize,
+}
+
+impl Tally {
+    fn is_passed(&self) -> bool {
+        self.yes >= self.required
+    }
+
+    /// 남은 사람이 모두 찬성해도 통과할 수 없음
+    fn is_rejected(&self) -> bool {
+        self.voters - self.no < self.required
+    }
+}
+
+/// 음성 채널에 있는 사람의 투표만 셈
+fn tally(vote: &Vote, listeners: &HashSet<UserId>) -> Tally {
+    let yes = vote.yes.intersection(listeners).count();
+    let no = vote.no.intersection(listeners).count();
+    let voters = listeners.len();
+
+    Tally {
+        yes,
+        no,
+        required: required(voters, vote.ratio),
+        voters,
+    }
+}
+
+fn render(vote: &Vote, listeners: &HashSet<UserId>) -> String {
+    let Tally {
+        yes, no, required, ..
+    } = tally(vote, listeners);
+
+    let mut r = MessageBuilder::new();
+
+    r.mention(&vote.user_id)
+        .push(" 님이 재생 중인 음악을 ")
+        .push(vote.action.name())
+        .push("하자고 해요");
+
+    if !vote.title.is_empty() {
+        r.push("\n").push_bold_safe(&vote.title);
+    }
+
+    r.push(format!(
+        "\n찬성 {yes} / {required} · 반대 {no} · {}초 동안 투표할 수 있어요",
+        vote.timeout.as_secs()
+    ));
+
+    r.build()
+}
+
+#[test]
+fn test_required() {
+    assert_eq!(required(1, 50), 1);
+    assert_eq!(required(2, 50), 2);
+    assert_eq!(required(3, 50), 2);
+    assert_eq!(required(4, 50), 3);
+    assert_eq!(required(4, 99), 4);
+    assert_eq!(required(10, 1), 1);
+}
@@ -0,0 +1,23 @@
+use serenity::prelude::Context;
+
+use crate::{
+    audio::ytdlp::{self, Update},
+    cfg::Cfg,
+};
+
+pub async fn update(ctx: &Context) -> crate::Result<String> {
+    let release_url = {
+        let x = ctx.data.read().await;
+        x.get::<Cfg>().unwrap().ytdlp_release_url.clone()
+    };
+
+    let r = match ytdlp::update(&release_url).await? {
+        Update::Updated { from, to } => format!(
+            "yt-dlp를 업데이트했어요 : {} → {to}",
+            from.as_deref().unwrap_or("알 수 없음")
+        ),
+        Update::AlreadyLatest(version) => format!("이미 최신 버전이에요 : {version}"),
+    };
+
+    Ok(r)
+}
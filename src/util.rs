@@ -50,6 +50,65 @@ pub mod time {
         Time(hours, minutes, seconds)
     }
 
+    /// 1:23:45, 23:45, 90, 90s, 2m, 1h2m3s, 1시간 2분 3초
+    pub fn parse_human_duration(x: &str) -> Option<Duration> {
+        let x = x.trim();
+
+        if x.contains(':') {
+            let xs = x
+                .split(':')
+                .map(|x| x.trim().parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            // 분, 초는 60보다 작아야 함
+            if xs.len() > 3 || xs.iter().skip(1).any(|x| *x >= 60) {
+                return None;
+            }
+
+            // 너무 큰 수를 입력하면 None
+            let seconds = xs
+                .into_iter()
+                .try_fold(0u64, |acc, x| acc.checked_mul(60)?.checked_add(x))?;
+
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let x = x.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+
+        if let Ok(seconds) = x.parse() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let mut seconds = 0;
+        let mut chars = x.chars().peekable();
+
+        while chars.peek().is_some() {
+            let mut n = String::new();
+            let mut unit = String::new();
+
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                n.push(c);
+            }
+
+            while let Some(c) = chars.next_if(|c| !c.is_ascii_digit()) {
+                unit.push(c);
+            }
+
+            let n: u64 = n.parse().ok()?;
+
+            let unit = match unit.to_lowercase().as_str() {
+                "h" | "시간" => 60 * 60,
+                "m" | "min" | "분" => 60,
+                "s" | "sec" | "초" => 1,
+                _ => return None,
+            };
+
+            seconds = n.checked_mul(unit).and_then(|x| x.checked_add(seconds))?;
+        }
+
+        Some(Duration::from_secs(seconds))
+    }
+
     pub fn parse_iso8601_duration(x: &str) -> Option<Duration> {
         // PT#H#M#S
         // P#DT#H#M#S
@@ -126,4 +185,23 @@ pub mod time {
 
         assert_eq!(x, Duration::from_secs(61_200 + 1_980));
     }
+
+    #[test]
+    fn test_parse_human_duration() {
+        let f = |x| parse_human_duration(x).map(|x| x.as_secs());
+
+        assert_eq!(f("1:23:45"), Some(5025));
+        assert_eq!(f("23:45"), Some(1425));
+        assert_eq!(f("90"), Some(90));
+        assert_eq!(f("90s"), Some(90));
+        assert_eq!(f("2m"), Some(120));
+        assert_eq!(f("1h2m3s"), Some(3723));
+        assert_eq!(f("1시간 2분 3초"), Some(3723));
+        assert_eq!(f("1:60"), None);
+        assert_eq!(f("2x"), None);
+        assert_eq!(f("m"), None);
+        assert_eq!(f("18446744073709551615:59:59"), None);
+        assert_eq!(f("18446744073709551615h"), None);
+        assert_eq!(f("18446744073709551615s1s"), None);
+    }
 }